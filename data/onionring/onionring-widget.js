@@ -0,0 +1,82 @@
+// Generated by ringfairy. A drop-in replacement for onionring.js's onionring-widget.js:
+// it reads `sites`, `ringName`, `ringID`, `useIndex`, `indexPage` and `useRandom` from onionring-variables.js,
+// and draws the same markup (so onionring.css still applies) into the element with the id `ringID`.
+//
+//   <div id="ringID"></div>
+//   <script type="text/javascript" src="https://ring.tld/onionring-variables.js"></script>
+//   <script type="text/javascript" src="https://ring.tld/onionring-widget.js"></script>
+
+var tag = document.getElementById(ringID);
+var thisSite = window.location.href;
+var thisIndex = null;
+
+for (var i = 0; i < sites.length; i++) {
+  if (thisSite.startsWith(sites[i])) {
+    thisIndex = i;
+    break;
+  }
+}
+
+function randomSite() {
+  var otherSites = sites.filter(function (site, index) { return index !== thisIndex; });
+  if (otherSites.length > 0) {
+    window.location.href = otherSites[Math.floor(Math.random() * otherSites.length)];
+  }
+}
+
+function webringLink(href, text) {
+  var link = document.createElement('a');
+  link.href = href;
+  link.textContent = text;
+  return link;
+}
+
+function webringCell(className) {
+  var cell = document.createElement('td');
+  cell.className = className;
+  return cell;
+}
+
+if (tag) {
+  var table = document.createElement('table');
+  var row = table.insertRow();
+
+  if (thisIndex === null) {
+    row.insertCell().textContent = "This site isn't part of the " + ringName + " webring yet. You should talk to the manager to have your site added to the list!";
+  } else {
+    var previousIndex = (thisIndex - 1 < 0) ? sites.length - 1 : thisIndex - 1;
+    var nextIndex = (thisIndex + 1 >= sites.length) ? 0 : thisIndex + 1;
+
+    var previousCell = webringCell('webring-prev');
+    previousCell.appendChild(webringLink(sites[previousIndex], '←'));
+    row.appendChild(previousCell);
+
+    var infoCell = webringCell('webring-info');
+    infoCell.appendChild(document.createTextNode('This site is part of the ' + ringName + ' webring'));
+    infoCell.appendChild(document.createElement('br'));
+    var links = document.createElement('span');
+    links.className = 'webring-links';
+    if (useRandom) {
+      var random = webringLink('javascript:void(0)', 'random');
+      random.addEventListener('click', function (event) {
+        event.preventDefault();
+        randomSite();
+      });
+      links.appendChild(random);
+    }
+    if (useIndex) {
+      if (links.childNodes.length > 0) {
+        links.appendChild(document.createTextNode(' | '));
+      }
+      links.appendChild(webringLink(indexPage, 'index'));
+    }
+    infoCell.appendChild(links);
+    row.appendChild(infoCell);
+
+    var nextCell = webringCell('webring-next');
+    nextCell.appendChild(webringLink(sites[nextIndex], '→'));
+    row.appendChild(nextCell);
+  }
+
+  tag.insertAdjacentElement('afterbegin', table);
+}
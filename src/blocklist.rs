@@ -0,0 +1,232 @@
+use regex::Regex;
+
+use crate::cli::AppSettings;
+use crate::error::Error;
+use crate::file::acquire_file_data;
+use crate::website::{canonicalize_url, Website};
+
+/// What a blocklist entry matches
+#[derive(Debug)]
+pub enum BlockRule {
+    /// A domain, along with all its subdomains
+    Domain(String),
+    /// A URL, where `*` matches anything. Compared the way `canonicalize_url` writes URLs, so the scheme and 'www.' don't matter.
+    Url(String, Regex),
+    /// An owner, compared ignoring case, word by word. Matches anywhere in the site's owner, e.g. 'Jane (@jane@example.social)'
+    Owner(String),
+}
+
+#[derive(Debug)]
+pub struct BlockEntry {
+    pub rule: BlockRule,
+    /// Why it's blocked, from a `# comment` after the entry
+    pub reason: Option<String>,
+    /// Which list & line it came from
+    pub location: String,
+}
+
+impl BlockEntry {
+    fn matches(&self, website: &Website) -> bool {
+        match &self.rule {
+            BlockRule::Domain(domain) => [Some(&website.url), website.gemini_url.as_ref(), website.gopher_url.as_ref()]
+                .into_iter()
+                .flatten()
+                .filter_map(|url| url::Url::parse(url).ok())
+                .filter_map(|url| url.host_str().map(str::to_lowercase))
+                .any(|host| host == *domain || host.ends_with(&format!(".{}", domain))),
+            BlockRule::Url(_, pattern) => pattern.is_match(&canonicalize_url(&website.url)),
+            BlockRule::Owner(owner) => website.owner.as_deref().is_some_and(|site_owner| {
+                let (blocked, tokens) = (owner_tokens(owner), owner_tokens(site_owner));
+                !blocked.is_empty()
+                    && tokens.windows(blocked.len()).any(|window| {
+                        window.iter().zip(&blocked).all(|(a, b)| a.eq_ignore_ascii_case(b))
+                    })
+            }),
+        }
+    }
+
+    /// Explains the entry for error messages, e.g. "the domain 'spam.tld' (blocklist.txt, line 3: resubmitted spam)"
+    pub fn describe(&self) -> String {
+        let rule = match &self.rule {
+            BlockRule::Domain(domain) => format!("the domain '{}'", domain),
+            BlockRule::Url(pattern, _) => format!("the URL pattern '{}'", pattern),
+            BlockRule::Owner(owner) => format!("the owner '{}'", owner),
+        };
+        match &self.reason {
+            Some(reason) => format!("{} ({}: {})", rule, self.location, reason),
+            None => format!("{} ({})", rule, self.location),
+        }
+    }
+}
+
+/// Splits an owner field into words & handles, on whitespace and punctuation which can't be part of a handle or address
+fn owner_tokens(owner: &str) -> Vec<&str> {
+    owner
+        .split(|c: char| c.is_whitespace() || "()[]{}<>,;|\"'".contains(c))
+        .map(|token| token.trim_end_matches(['.', ':', '!', '?']))
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+/// Domains, URLs and owners which aren't allowed in the ring
+#[derive(Debug, Default)]
+pub struct Blocklist {
+    pub entries: Vec<BlockEntry>,
+}
+
+impl Blocklist {
+    /// Reads a blocklist: one entry per line, as `domain:`, `url:` or `owner:` followed by the value.
+    /// A bare value is a domain. Anything after ` #` is the reason, and lines starting with `#` are comments.
+    pub fn parse(data: &str, source: &str) -> Result<Self, Error> {
+        let mut entries = Vec::new();
+        for (index, line) in data.lines().enumerate() {
+            let location = format!("{}, line {}", source, index + 1);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (entry, reason) = match line.split_once(" #") {
+                Some((entry, reason)) => (entry.trim(), Some(reason.trim().to_string()).filter(|r| !r.is_empty())),
+                None => (line, None),
+            };
+
+            let (kind, value) = match entry.split_once(':') {
+                Some((kind @ ("domain" | "url" | "owner"), value)) => (kind, value.trim()),
+                _ => ("domain", entry),
+            };
+            let rule = match kind {
+                "url" => BlockRule::Url(value.to_string(), url_pattern(value, &location)?),
+                "owner" => BlockRule::Owner(value.to_string()),
+                _ => {
+                    let domain = value.trim_end_matches('.').to_lowercase();
+                    if domain.is_empty() || domain.contains(['/', ' ', '*']) {
+                        return Err(Error::StringError(format!(
+                            "Invalid blocklist entry '{}' at {}: not a domain (use 'url:' for URL patterns)",
+                            entry, location
+                        )));
+                    }
+                    BlockRule::Domain(domain.strip_prefix("www.").unwrap_or(&domain).to_string())
+                }
+            };
+            entries.push(BlockEntry { rule, reason, location });
+        }
+        Ok(Blocklist { entries })
+    }
+
+    /// The first entry the site matches, if any
+    pub fn find(&self, website: &Website) -> Option<&BlockEntry> {
+        self.entries.iter().find(|entry| entry.matches(website))
+    }
+
+    /// Leaves out any blocked sites, noting each one in the log
+    pub fn drop_blocked(&self, websites: Vec<Website>) -> Vec<Website> {
+        websites
+            .into_iter()
+            .filter(|website| match self.find(website) {
+                Some(entry) => {
+                    log::info!("Leaving out blocked site: {} - matches {}", website.url, entry.describe());
+                    false
+                }
+                None => true,
+            })
+            .collect()
+    }
+}
+
+/// Turns a URL pattern into a regex over canonical URLs (see `canonicalize_url`)
+fn url_pattern(pattern: &str, location: &str) -> Result<Regex, Error> {
+    let canonical = pattern.trim().to_lowercase();
+    let canonical = canonical
+        .strip_prefix("https://")
+        .or_else(|| canonical.strip_prefix("http://"))
+        .unwrap_or(&canonical);
+    let canonical = canonical.strip_prefix("www.").unwrap_or(canonical).trim_end_matches('/');
+    if canonical.is_empty() {
+        return Err(Error::StringError(format!("Empty URL pattern in the blocklist at {}", location)));
+    }
+
+    let regex = canonical.split('*').map(regex::escape).collect::<Vec<_>>().join(".*");
+    Regex::new(&format!("(?i)^{}$", regex))
+        .map_err(|e| Error::StringError(format!("Invalid URL pattern '{}' at {}: {}", pattern, location, e)))
+}
+
+/// Reads every blocklist in the settings into one
+pub async fn load_blocklist(settings: &AppSettings) -> Result<Blocklist, Error> {
+    let mut blocklist = Blocklist::default();
+    for source in &settings.blocklists {
+        let data = acquire_file_data(source).await?;
+        blocklist.entries.extend(Blocklist::parse(&data, source)?.entries);
+    }
+    if !blocklist.entries.is_empty() {
+        log::info!("Loaded {} blocklist entries", blocklist.entries.len());
+    }
+    Ok(blocklist)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site(url: &str, owner: Option<&str>) -> Website {
+        Website {
+            owner: owner.map(String::from),
+            ..Website::sample("", url)
+        }
+    }
+
+    #[test]
+    fn test_parse_blocklist() {
+        let data = "# spam we've had to remove\n\
+                    spam.tld # resubmitted three times\n\
+                    \n\
+                    domain: www.junk.tld\n\
+                    url: https://host.tld/~*/casino\n\
+                    owner: Spammer@Example.com\n";
+        let blocklist = Blocklist::parse(data, "blocklist.txt").unwrap();
+        assert_eq!(blocklist.entries.len(), 4);
+
+        let entry = blocklist.find(&site("https://shop.spam.tld/", None)).unwrap();
+        assert_eq!(
+            entry.describe(),
+            "the domain 'spam.tld' (blocklist.txt, line 2: resubmitted three times)"
+        );
+        assert!(blocklist.find(&site("https://notspam.tld", None)).is_none());
+        assert!(blocklist.find(&site("http://junk.tld/page", None)).is_some());
+
+        let entry = blocklist.find(&site("http://www.host.tld/~someone/Casino/", None)).unwrap();
+        assert_eq!(entry.describe(), "the URL pattern 'https://host.tld/~*/casino' (blocklist.txt, line 5)");
+        assert!(blocklist.find(&site("https://host.tld/~someone/blog", None)).is_none());
+
+        assert!(blocklist.find(&site("https://new.tld", Some(" spammer@example.COM"))).is_some());
+        assert!(blocklist.find(&site("https://new.tld", Some("someone@example.com"))).is_none());
+    }
+
+    #[test]
+    fn test_block_owner_within_owner_field() {
+        let blocklist = Blocklist::parse("owner: @jane@example.social
+owner: Spam Co", "blocklist.txt").unwrap();
+
+        assert!(blocklist.find(&site("https://new.tld", Some("Jane (@jane@example.social)"))).is_some());
+        assert!(blocklist.find(&site("https://new.tld", Some("@Jane@example.social, Jane"))).is_some());
+        assert!(blocklist.find(&site("https://new.tld", Some("The spam co."))).is_some());
+        assert!(blocklist.find(&site("https://new.tld", Some("Jane (@jane@example.social.net)"))).is_none());
+        assert!(blocklist.find(&site("https://new.tld", Some("Spam Company"))).is_none());
+        assert!(blocklist.find(&site("https://new.tld", Some("Jane"))).is_none());
+    }
+
+    #[test]
+    fn test_parse_blocklist_errors() {
+        let result = Blocklist::parse("spam.tld\nhttps://spam.tld/page\n", "blocklist.txt");
+        assert!(result.unwrap_err().to_string().contains("blocklist.txt, line 2"));
+        assert!(Blocklist::parse("url: https://", "blocklist.txt").is_err());
+    }
+
+    #[test]
+    fn test_drop_blocked() {
+        let blocklist = Blocklist::parse("spam.tld", "blocklist.txt").unwrap();
+        let websites = vec![site("https://spam.tld", None), site("https://good.tld", None)];
+        let kept = blocklist.drop_blocked(websites);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].url, "https://good.tld");
+    }
+}
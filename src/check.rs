@@ -0,0 +1,406 @@
+use std::fs;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+
+use crate::cli::AppSettings;
+use crate::error::Error;
+use crate::file::parse_website_list;
+use crate::blocklist::load_blocklist;
+use crate::gen::webring::prepare_websites;
+use crate::http::{setup_client, setup_client_without_redirects, MAX_REDIRECTS};
+use crate::website::{
+    audit_document, canonicalize_url, contains_link, expected_links, has_onionring_widget,
+    ownership_token, Website,
+};
+
+/// One request made while fetching a page
+pub struct Hop {
+    pub url: String,
+    pub status: reqwest::StatusCode,
+    pub elapsed: Duration,
+}
+
+/// A page fetched hop by hop, so the redirects can be shown
+pub struct FetchedPage {
+    pub hops: Vec<Hop>,
+    pub elapsed: Duration,
+    pub body: String,
+}
+
+/// Something on the page the audit looks at for next/previous links
+pub struct LinkCandidate {
+    /// Where it was found, e.g. `<a href>`
+    pub source: &'static str,
+    pub link: String,
+    /// Whether it counts, and if not, why not
+    pub verdict: String,
+}
+
+/// Finds the site to check, by slug or URL. Sites which aren't in the list yet are checked as if they'd just been added.
+pub fn find_site(target: &str, websites: &[Website]) -> Option<Website> {
+    websites
+        .iter()
+        .find(|website| website.slug == target)
+        .or_else(|| {
+            let target = canonicalize_url(target);
+            websites.iter().find(|website| canonicalize_url(&website.url) == target)
+        })
+        .cloned()
+}
+
+/// Fetches a page one request at a time, following up to `MAX_REDIRECTS` redirects
+pub async fn fetch_page(client: &reqwest::Client, url: &str) -> Result<FetchedPage, Error> {
+    let started = Instant::now();
+    let mut url = url::Url::parse(url).map_err(|e| Error::StringError(format!("Invalid URL '{}': {}", url, e)))?;
+    let mut hops = Vec::new();
+
+    loop {
+        let hop_started = Instant::now();
+        let response = client.get(url.clone()).send().await?;
+        let status = response.status();
+        hops.push(Hop {
+            url: url.to_string(),
+            status,
+            elapsed: hop_started.elapsed(),
+        });
+
+        if !status.is_redirection() {
+            let body = response.text().await?;
+            return Ok(FetchedPage {
+                hops,
+                elapsed: started.elapsed(),
+                body,
+            });
+        }
+        if hops.len() > MAX_REDIRECTS {
+            return Err(Error::StringError(format!(
+                "Gave up after {} redirects; the audit would too",
+                MAX_REDIRECTS
+            )));
+        }
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .ok_or_else(|| Error::StringError(format!("{} redirected without saying where to", url)))?;
+        url = url
+            .join(location)
+            .map_err(|e| Error::StringError(format!("Invalid redirect to '{}': {}", location, e)))?;
+    }
+}
+
+/// Explains whether a link counts as the site's next/previous link, and if not, what's wrong with it
+pub fn diagnose_link(link: &str, page_url: &str, website: &Website, settings: &AppSettings) -> String {
+    let (next_links, prev_links) = expected_links(website, settings);
+    let trimmed = link.trim_end_matches('/');
+    if next_links.iter().any(|expected| trimmed == expected) {
+        return "OK: the next link".to_string();
+    }
+    if prev_links.iter().any(|expected| trimmed == expected) {
+        return "OK: the previous link".to_string();
+    }
+
+    let expected: Vec<&String> = next_links.iter().chain(&prev_links).collect();
+    if expected.iter().any(|expected| link.trim().trim_end_matches('/') == expected.as_str()) {
+        return "has spaces around the address; remove them".to_string();
+    }
+
+    // The audit compares the link as written, so relative links never match
+    let absolute = url::Url::parse(page_url)
+        .and_then(|page| page.join(link.trim()))
+        .map(|url| url.to_string())
+        .unwrap_or_else(|_| link.trim().to_string());
+    let is_relative = !link.trim().starts_with("http://") && !link.trim().starts_with("https://");
+    if is_relative {
+        let path = link.trim().trim_end_matches('/');
+        let same_path = |expected: &&&String| {
+            absolute.trim_end_matches('/') == expected.as_str()
+                || url::Url::parse(expected).is_ok_and(|url| url.path().trim_end_matches('/') == path)
+        };
+        if let Some(expected) = expected.iter().find(same_path) {
+            return format!("a relative link; write out the full address, {}", expected);
+        }
+    }
+
+    let canonical = canonicalize_url(&absolute);
+    if let Some(expected) = expected.iter().find(|expected| canonicalize_url(expected) == canonical) {
+        return format!(
+            "close, but the audit needs exactly {} (check http/https, 'www.' and capitals)",
+            expected
+        );
+    }
+
+    let ring = canonicalize_url(&settings.base_url);
+    let Some(path) = canonical.strip_prefix(&ring).filter(|_| !ring.is_empty()) else {
+        return "not a link to the ring".to_string();
+    };
+    let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+    let is_direction = |text: &str| {
+        text.eq_ignore_ascii_case(&settings.next_url_text) || text.eq_ignore_ascii_case(&settings.prev_url_text)
+    };
+    match segments.as_slice() {
+        [slug, direction] if is_direction(direction) && !slug.eq_ignore_ascii_case(&website.slug) => format!(
+            "goes to the '{}' site's {} page; this site's slug is '{}'",
+            slug, direction, website.slug
+        ),
+        [slug, page] if slug.eq_ignore_ascii_case(&website.slug) => format!(
+            "goes to this site's '{}' page, rather than '{}' or '{}'",
+            page, settings.next_url_text, settings.prev_url_text
+        ),
+        [direction] if is_direction(direction) => {
+            "a universal link, which this ring doesn't use; link to the site's own pages instead".to_string()
+        }
+        _ => "goes to the ring, but not to a next/previous page".to_string(),
+    }
+}
+
+/// Lists every link the audit considers, with a verdict on each
+pub fn link_candidates(document: &scraper::Html, page_url: &str, website: &Website, settings: &AppSettings) -> Vec<LinkCandidate> {
+    let mut candidates = Vec::new();
+    let (Ok(anchor_selector), Ok(onclick_selector), Ok(url_pattern)) = (
+        scraper::Selector::parse("a[href]"),
+        scraper::Selector::parse("button[onclick], img[onclick]"),
+        Regex::new(r#"https?://[^\s'"`)]+"#),
+    ) else {
+        return candidates;
+    };
+
+    for element in document.select(&anchor_selector) {
+        let link = element.value().attr("href").unwrap_or_default();
+        candidates.push(LinkCandidate {
+            source: "<a href>",
+            link: link.to_string(),
+            verdict: diagnose_link(link, page_url, website, settings),
+        });
+    }
+
+    let (next_links, prev_links) = expected_links(website, settings);
+    for element in document.select(&onclick_selector) {
+        let onclick = element.value().attr("onclick").unwrap_or_default();
+        let source = if element.value().name() == "img" {
+            "<img onclick>"
+        } else {
+            "<button onclick>"
+        };
+        let verdict = if contains_link(onclick, &next_links) {
+            "OK: the next link".to_string()
+        } else if contains_link(onclick, &prev_links) {
+            "OK: the previous link".to_string()
+        } else {
+            match url_pattern.find(onclick) {
+                Some(url) => diagnose_link(url.as_str(), page_url, website, settings),
+                None => "no full address in it".to_string(),
+            }
+        };
+        candidates.push(LinkCandidate {
+            source,
+            link: onclick.to_string(),
+            verdict,
+        });
+    }
+
+    candidates
+}
+
+/// Runs the audit on one site, explaining each step. `html_path` checks a local copy of the page instead of fetching it.
+pub async fn check_site(target: &str, html_path: Option<&str>, settings: &AppSettings) -> Result<(), Error> {
+    let mut websites = parse_website_list(settings).await?;
+    let is_url = target.starts_with("http://") || target.starts_with("https://");
+    if is_url && find_site(target, &websites).is_none() {
+        println!("{} isn't in the website list yet; checking it as if it had just been added", target);
+        websites.push(Website {
+            url: target.to_string(),
+            ..Default::default()
+        });
+    }
+
+    // The site goes through the same steps as in a build, so it's checked exactly as the build would see it
+    let blocklist = load_blocklist(settings).await?;
+    let websites = prepare_websites(websites, &blocklist, settings)?;
+    let Some(website) = find_site(target, &websites) else {
+        let hint = if blocklist.entries.is_empty() { "" } else { ", or it was left out by the blocklist" };
+        return Err(Error::StringError(format!(
+            "No site with the slug or URL '{}' in the website list{}",
+            target, hint
+        )));
+    };
+
+    println!("Checking '{}' ({})", website.slug, website.url);
+    if !website.is_active_on(chrono::Local::now().date_naive()) {
+        println!("Note: going by its status and dates, this site isn't part of the ring today, so it's left out of builds");
+    }
+    let (next_links, prev_links) = expected_links(&website, settings);
+    println!("Expected next link: {}", next_links.join(" or "));
+    println!("Expected previous link: {}", prev_links.join(" or "));
+
+    let html = match html_path {
+        Some(path) => {
+            println!("\nReading {} instead of fetching the site", path);
+            fs::read_to_string(path)?
+        }
+        None => {
+            println!("\nFetching:");
+            let client = setup_client_without_redirects(settings).await?;
+            let page = fetch_page(&client, &website.url).await?;
+            for hop in &page.hops {
+                println!("  {} {} ({} ms)", hop.status.as_u16(), hop.url, hop.elapsed.as_millis());
+            }
+            println!("  Took {} ms in all; {} bytes", page.elapsed.as_millis(), page.body.len());
+            if let Some(last) = page.hops.last().filter(|hop| !hop.status.is_success()) {
+                println!(
+                    "  The final answer was {}, so this probably isn't the page you meant; the audit reads it anyway",
+                    last.status
+                );
+            }
+            page.body
+        }
+    };
+
+    let document = scraper::Html::parse_document(&html);
+    let candidates = link_candidates(&document, &website.url, &website, settings);
+    println!("\nLinks found ({}):", candidates.len());
+    for candidate in &candidates {
+        println!("  {} {}\n      {}", candidate.source, candidate.link, candidate.verdict);
+    }
+
+    if settings.onionring {
+        let found = has_onionring_widget(&document, &website.url, settings);
+        println!("\nonionring widget: {}", if found { "found" } else { "not found" });
+    }
+
+    // The verdict comes from the same audit the build runs
+    let client = match html_path {
+        Some(_) => None,
+        None => Some(setup_client(settings).await?),
+    };
+    let (audited, failure_reason) = audit_document(client.as_ref(), &document, &website, settings).await;
+    if let (Some(token), Some(verified)) = (ownership_token(&website.slug, settings), audited.verified) {
+        println!(
+            "\nOwnership: {}",
+            if verified { "verified".to_string() } else { format!("token {} not found", token) }
+        );
+    }
+
+    match failure_reason {
+        None => {
+            println!("\nPASSED the audit");
+            Ok(())
+        }
+        Some(reason) => Err(Error::StringError(format!("'{}' FAILED the audit: {}", website.slug, reason.trim()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::website::find_ring_links;
+
+    fn settings() -> AppSettings {
+        AppSettings {
+            base_url: "https://ring.tld".into(),
+            next_url_text: "next".into(),
+            prev_url_text: "previous".into(),
+            ..Default::default()
+        }
+    }
+
+    fn site() -> Website {
+        Website::sample("mine", "https://mine.tld/")
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_link() {
+        let settings = settings();
+        let verdict = |link: &str| diagnose_link(link, "https://ring.tld/page", &site(), &settings);
+
+        assert_eq!(verdict("https://ring.tld/mine/next/"), "OK: the next link");
+        assert_eq!(verdict("https://ring.tld/mine/previous"), "OK: the previous link");
+        assert!(verdict(" https://ring.tld/mine/next ").contains("spaces"));
+        assert!(verdict("/mine/next").contains("relative"));
+        assert!(diagnose_link("/mine/next", "https://mine.tld/", &site(), &settings).contains("https://ring.tld/mine/next"));
+        assert!(verdict("http://www.ring.tld/mine/next").contains("close"));
+        assert!(verdict("https://ring.tld/other/next").contains("'other' site's next page"));
+        assert!(verdict("https://ring.tld/mine/random").contains("this site's 'random' page"));
+        assert!(verdict("https://ring.tld/next").contains("universal"));
+        assert!(verdict("https://ring.tld/").contains("not to a next/previous page"));
+        assert_eq!(verdict("https://elsewhere.tld/"), "not a link to the ring");
+    }
+
+    #[tokio::test]
+    async fn test_link_candidates() {
+        let settings = settings();
+        let document = scraper::Html::parse_document(
+            r#"<a href="https://ring.tld/mine/next">next</a>
+            <img src="prev.png" onclick="window.location.href='https://ring.tld/mine/previous'">
+            <button onclick="go('https://ring.tld/someone/next')">?</button>"#,
+        );
+
+        let candidates = link_candidates(&document, "https://mine.tld/", &site(), &settings);
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(candidates[0].verdict, "OK: the next link");
+        assert_eq!(candidates[1].source, "<img onclick>");
+        assert_eq!(candidates[1].verdict, "OK: the previous link");
+        assert!(candidates[2].verdict.contains("'someone' site's next page"));
+        assert_eq!(find_ring_links(&document, &site(), &settings), (true, true));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_page_follows_redirects() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/")
+            .with_status(301)
+            .with_header("location", "/home")
+            .create();
+        server.mock("GET", "/home").with_status(200).with_body("hello").create();
+
+        let client = setup_client_without_redirects(&settings()).await.unwrap();
+        let page = fetch_page(&client, &server.url()).await.unwrap();
+        assert_eq!(page.hops.len(), 2);
+        assert_eq!(page.hops[0].status.as_u16(), 301);
+        assert!(page.hops[1].url.ends_with("/home"));
+        assert_eq!(page.body, "hello");
+    }
+
+    #[tokio::test]
+    async fn test_check_site_matches_build() {
+        let dir = std::env::temp_dir().join(format!("ringfairy-check-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let list = dir.join("websites.json");
+        let page = dir.join("page.html");
+        fs::write(&list, r#"[{"slug": "", "url": "https://mine.tld/"}, {"slug": "other", "url": "https://other.tld"}]"#).unwrap();
+        // The slug-less member gets the slug a build would give it
+        fs::write(&page, r#"<a href="https://ring.tld/httpsminetld/next">n</a><a href="https://ring.tld/httpsminetld/previous">p</a>"#).unwrap();
+        let page = page.to_str().unwrap();
+
+        let mut settings = AppSettings {
+            filepath_list: vec![list.to_string_lossy().to_string()],
+            ..settings()
+        };
+        check_site("https://mine.tld", Some(page), &settings).await.unwrap();
+        assert!(check_site("httpsminetld", Some(page), &settings).await.is_ok());
+
+        // Tokens are required in builds, so they're required here too
+        settings.verification_secret = "secret".into();
+        settings.require_verified = true;
+        let error = check_site("httpsminetld", Some(page), &settings).await.unwrap_err();
+        assert!(error.to_string().contains("Missing ownership token"));
+
+        // Sites the build would refuse are refused here too
+        settings.require_verified = false;
+        fs::write(dir.join("blocklist.txt"), "other.tld").unwrap();
+        settings.blocklists = vec![dir.join("blocklist.txt").to_string_lossy().to_string()];
+        assert!(check_site("httpsminetld", Some(page), &settings).await.is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_find_site() {
+        let websites = vec![site()];
+        assert!(find_site("mine", &websites).is_some());
+        assert!(find_site("http://www.mine.tld", &websites).is_some());
+        assert!(find_site("someone", &websites).is_none());
+    }
+}
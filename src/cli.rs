@@ -1,8 +1,9 @@
 use crate::error::Error;
-use clap::{ArgAction, Parser};
+use clap::{ArgAction, Args, Parser, Subcommand};
 use serde::Deserialize;
 
 use crate::file;
+use crate::website::MemberStatus;
 
 // Main/final settings struct
 #[derive(Debug)]
@@ -14,25 +15,52 @@ pub struct AppSettings {
     pub _filepath_config: String,
     pub json_lists: Vec<String>,
     pub toml_lists: Vec<String>,
+    pub yaml_lists: Vec<String>,
     pub filepath_list: Vec<String>,
+    pub blocklists: Vec<String>,
+    pub blocklist_drop: bool,
+    pub private_fields: Vec<String>,
     pub filename_template_redirect: String,
+    pub filename_template_join: String,
+    pub filename_template_member: String,
+    pub filename_template_random: String,
+    pub filename_template_universal: String,
     pub path_output: String,
     pub path_assets: String,
     pub path_templates: String,
+    pub path_pending: String,
+    pub theme: String,
     pub base_url: String,
+    pub gemini_base_url: String,
+    pub path_output_gemini: String,
+    pub gopher_base_url: String,
+    pub path_output_gopher: String,
     pub next_url_text: String,
     pub prev_url_text: String,
+    pub random_url_text: String,
+    pub random_exclude: Vec<String>,
+    pub random_exclude_neighbors: bool,
+    pub universal_links: bool,
+    pub onionring: bool,
+    pub onionring_id: String,
+    pub server_configs: Vec<String>,
+    pub skip_redirect_pages: bool,
+    pub skip_seo: bool,
+    pub skip_builtin_templates: bool,
     pub client_user_agent: String,
     pub client_header: String,
     pub audit_retries_max: u64,
     pub audit_retries_delay: u64,
     pub audit: bool,
+    pub verification_secret: String,
+    pub require_verified: bool,
     pub no_slug: bool,
     pub shuffle: bool,
     pub _verbose: bool,
     pub skip_minify: bool,
     pub skip_verify: bool,
     pub dry_run: bool,
+    pub command: Option<Command>,
 }
 
 // Hardcoded values for anything not defined elsewhere
@@ -46,25 +74,52 @@ impl Default for AppSettings {
             _filepath_config: "./ringfairy.toml".into(),
             json_lists: Vec::new(),
             toml_lists: Vec::new(),
+            yaml_lists: Vec::new(),
             filepath_list: vec!["./websites.json".to_string()],
+            blocklists: Vec::new(),
+            blocklist_drop: false,
+            private_fields: Vec::new(),
             filename_template_redirect: "redirect.html".into(),
+            filename_template_join: "join.html".into(),
+            filename_template_member: "member.html".into(),
+            filename_template_random: "random.html".into(),
+            filename_template_universal: "universal.html".into(),
             path_output: "./webring".into(),
             path_assets: "./data/assets".into(),
             path_templates: "./data/templates".into(),
+            path_pending: "./pending".into(),
+            theme: "default".into(),
             base_url: " ".to_string(),
+            gemini_base_url: String::new(),
+            path_output_gemini: "./capsule".into(),
+            gopher_base_url: String::new(),
+            path_output_gopher: "./gopherhole".into(),
             next_url_text: "next".to_string(),
             prev_url_text: "previous".to_string(),
+            random_url_text: "random".to_string(),
+            random_exclude: Vec::new(),
+            random_exclude_neighbors: false,
+            universal_links: false,
+            onionring: false,
+            onionring_id: String::new(),
+            server_configs: Vec::new(),
+            skip_redirect_pages: false,
+            skip_seo: false,
+            skip_builtin_templates: false,
             client_user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/90.0.4430.212 Safari/537.36".into(),
             client_header: "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8".into(),
             audit_retries_delay: 100,
             audit_retries_max: 2,
             audit: false,
+            verification_secret: String::new(),
+            require_verified: false,
             no_slug: false,
             shuffle: false,
             _verbose: false,
             skip_minify: false,
             skip_verify: false,
             dry_run: false,
+            command: None,
         }
     }
 }
@@ -78,19 +133,45 @@ pub struct ConfigSettings {
     pub ring_owner_site: Option<String>,
     pub json_list: Option<Vec<String>>,
     pub toml_list: Option<Vec<String>>,
+    pub yaml_list: Option<Vec<String>>,
     pub filepath_list: Option<Vec<String>>,
+    pub blocklists: Option<Vec<String>>,
+    pub blocklist_drop: Option<bool>,
+    pub private_fields: Option<Vec<String>>,
     pub filename_template_redirect: Option<String>,
+    pub filename_template_join: Option<String>,
+    pub filename_template_member: Option<String>,
+    pub filename_template_random: Option<String>,
+    pub filename_template_universal: Option<String>,
     pub path_output: Option<String>,
     pub path_assets: Option<String>,
     pub path_templates: Option<String>,
+    pub path_pending: Option<String>,
+    pub theme: Option<String>,
     pub base_url: Option<String>,
+    pub gemini_base_url: Option<String>,
+    pub path_output_gemini: Option<String>,
+    pub gopher_base_url: Option<String>,
+    pub path_output_gopher: Option<String>,
     pub next_url_text: Option<String>,
     pub prev_url_text: Option<String>,
+    pub random_url_text: Option<String>,
+    pub random_exclude: Option<Vec<String>>,
+    pub random_exclude_neighbors: Option<bool>,
+    pub universal_links: Option<bool>,
+    pub onionring: Option<bool>,
+    pub onionring_id: Option<String>,
+    pub server_configs: Option<Vec<String>>,
+    pub skip_redirect_pages: Option<bool>,
+    pub skip_seo: Option<bool>,
+    pub skip_builtin_templates: Option<bool>,
     pub client_user_agent: Option<String>,
     pub client_header: Option<String>,
     pub audit_retries_max: Option<u64>,
     pub audit_retries_delay: Option<u64>,
     pub audit: Option<bool>,
+    pub verification_secret: Option<String>,
+    pub require_verified: Option<bool>,
     pub no_slug: Option<bool>,
     pub shuffle: Option<bool>,
     pub verbose: Option<bool>,
@@ -113,6 +194,9 @@ pub struct ClapSettings {
     )]
     pub input_path: Option<String>,
 
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+
     #[clap(
         short = 'c',
         long = "cfg",
@@ -136,6 +220,13 @@ pub struct ClapSettings {
     )]
     pub toml_list: Vec<String>,
 
+    #[clap(
+        short = 'Y',
+        long = "yaml-string",
+        help = "Website list as a YAML string"
+    )]
+    pub yaml_list: Vec<String>,
+
     #[clap(
         short = 'l',
         long = "list",
@@ -144,6 +235,23 @@ pub struct ClapSettings {
     )]
     pub filepath_list: Vec<String>,
 
+    #[clap(
+        long = "blocklist",
+        ignore_case = false,
+        help = "A file or URL listing blocked domains, URL patterns and owners, one per line. Sites matching it fail verification. Can be used multiple times."
+    )]
+    pub blocklists: Vec<String>,
+
+    #[clap(long = "blocklist-drop", action = ArgAction::SetTrue, help = "Quietly leaves out sites matching the blocklist, instead of failing the build.")]
+    pub blocklist_drop: bool,
+
+    #[clap(
+        long = "private-field",
+        ignore_case = false,
+        help = "A key in each site's 'misc' which is only for admins, e.g. 'email'. It's kept in the website list, but left out of every generated page and data file, like the 'private' table. Can be used multiple times."
+    )]
+    pub private_fields: Vec<String>,
+
     #[clap(
         short = 'r',
         long = "redirect-template",
@@ -155,10 +263,31 @@ pub struct ClapSettings {
     #[clap(
         long = "random-template",
         ignore_case = false,
-        help = "Specify the file used for the random redirect feature. Default is 'random.html'."
+        help = "Specify the file used for the random redirect feature. Besides the top-level random page, it's used to build a random page for each site, which never sends visitors back to the site they came from. Default is 'random.html'."
     )]
     pub filename_template_random: Option<String>,
 
+    #[clap(
+        long = "join-template",
+        ignore_case = false,
+        help = "Specify the file used to build each site's join page, showing the exact embed code for that site. Like the redirect template, it's skipped when building the rest of the custom templates. Default is 'join.html'."
+    )]
+    pub filename_template_join: Option<String>,
+
+    #[clap(
+        long = "member-template",
+        ignore_case = false,
+        help = "Specify the (optional) file used to build each site's landing page at /slug/, with the site and its previous/next neighbors available. Skipped when building the rest of the custom templates. Default is 'member.html'."
+    )]
+    pub filename_template_member: Option<String>,
+
+    #[clap(
+        long = "universal-template",
+        ignore_case = false,
+        help = "Specify the file used to build the universal next/previous pages, which look up the visitor's referrer instead of needing a slug. Only used with --universal-links. Default is 'universal.html'."
+    )]
+    pub filename_template_universal: Option<String>,
+
     #[clap(
         short = 'o',
         long = "output",
@@ -183,6 +312,20 @@ pub struct ClapSettings {
     )]
     pub path_templates: Option<String>,
 
+    #[clap(
+        long = "pending",
+        ignore_case = false,
+        help = "The folder where join requests wait for `ringfairy review`, one member file each (JSON, TOML, YAML or Markdown)."
+    )]
+    pub path_pending: Option<String>,
+
+    #[clap(
+        long = "theme",
+        ignore_case = false,
+        help = "Choose a built-in theme: 'default', 'light' or 'terminal'. Its stylesheet is written to the output folder, unless the assets folder has its own 'styles.css'."
+    )]
+    pub theme: Option<String>,
+
     #[clap(
         short = 'u',
         long = "url",
@@ -191,6 +334,34 @@ pub struct ClapSettings {
     )]
     pub base_url: Option<String>,
 
+    #[clap(
+        long = "gemini-url",
+        ignore_case = false,
+        help = "The base URL for the Gemini mirror of the webring, e.g. 'gemini://example.com'. If set, a capsule is generated for all sites with a 'gemini_url'. Use --server-config gmid for real next/previous redirects."
+    )]
+    pub gemini_base_url: Option<String>,
+
+    #[clap(
+        long = "gemini-output",
+        ignore_case = false,
+        help = "Define the output directory for the Gemini capsule. Default is './capsule'."
+    )]
+    pub path_output_gemini: Option<String>,
+
+    #[clap(
+        long = "gopher-url",
+        ignore_case = false,
+        help = "The base URL for the Gopher mirror of the webring, e.g. 'gopher://example.com/1/ring'. If set, a gopher hole is generated for all sites with a 'gopher_url'."
+    )]
+    pub gopher_base_url: Option<String>,
+
+    #[clap(
+        long = "gopher-output",
+        ignore_case = false,
+        help = "Define the output directory for the gopher hole. Default is './gopherhole'."
+    )]
+    pub path_output_gopher: Option<String>,
+
     #[clap(
         long = "next-text",
         ignore_case = false,
@@ -205,6 +376,46 @@ pub struct ClapSettings {
     )]
     pub prev_url_text: Option<String>,
 
+    #[clap(
+        long = "random-text",
+        ignore_case = false,
+        help = "The string for 'random' in each site's random redirect URL, e.g. `https://example.com/site/random`"
+    )]
+    pub random_url_text: Option<String>,
+
+    #[clap(
+        long = "random-exclude",
+        ignore_case = false,
+        help = "Slug of a site which should never be picked as a random destination. Can be used multiple times."
+    )]
+    pub random_exclude: Vec<String>,
+
+    #[clap(long = "random-exclude-neighbors", action = ArgAction::SetTrue, help = "Also prevents each site's random page from picking its next/previous neighbors.")]
+    pub random_exclude_neighbors: bool,
+
+    #[clap(long = "universal-links", action = ArgAction::SetTrue, help = "Generates top-level next/previous pages (e.g. `https://example.com/next`) which work out where the visitor came from, so every member can use the same links. The audit will also accept these links.")]
+    pub universal_links: bool,
+
+    #[clap(long = "onionring", action = ArgAction::SetTrue, help = "Also generates onionring-variables.js and onionring-widget.js, so members' onionring.js embeds work with this ring. The audit will also accept the onionring widget.")]
+    pub onionring: bool,
+
+    #[clap(
+        long = "onionring-id",
+        ignore_case = false,
+        help = "The 'ringID' for the onionring widget, i.e. the id of the element it's drawn in. Defaults to the ring's name, minus anything but letters & numbers."
+    )]
+    pub onionring_id: Option<String>,
+
+    #[clap(
+        long = "server-config",
+        ignore_case = false,
+        help = "Also writes redirect rules for a web server, so hosts with native redirects don't need an HTML page per site & direction. One of 'netlify' (_redirects, also used by Cloudflare Pages), 'apache' (.htaccess), 'nginx' or 'caddy', or 'gmid' for the Gemini capsule. Can be used multiple times."
+    )]
+    pub server_configs: Vec<String>,
+
+    #[clap(long = "skip-redirect-pages", action = ArgAction::SetTrue, help = "Skips generating the HTML next/previous pages for each site. Only takes effect alongside --server-config.")]
+    pub skip_redirect_pages: bool,
+
     #[clap(
         short = 'n',
         long = "name",
@@ -254,6 +465,16 @@ pub struct ClapSettings {
     )]
     pub audit_retries_delay: Option<u64>,
 
+    #[clap(
+        long = "verification-secret",
+        ignore_case = false,
+        help = "When auditing sites, also checks each one for its ownership token, which is worked out from its slug and this secret. Members put it in a <meta name=\"webring-verification\"> tag or a /.well-known/webring-verification file."
+    )]
+    pub verification_secret: Option<String>,
+
+    #[clap(long = "require-verified", action = ArgAction::SetTrue, help = "When auditing sites, leaves out any site whose ownership token can't be found. Needs --verification-secret.")]
+    pub require_verified: bool,
+
     #[clap(
         short = 'U',
         long = "client-user-agent",
@@ -280,6 +501,12 @@ pub struct ClapSettings {
     #[clap(long = "skip-minification", action = ArgAction::SetTrue, help = "Skips 'minification' of HTML files, which tries to reduce their file size. If your generated HTML files are having issues, try skipping minification.")]
     pub skip_minify: bool,
 
+    #[clap(long = "skip-seo", action = ArgAction::SetTrue, help = "Skips generating 'sitemap.xml' and 'robots.txt', and adding noindex/canonical tags to redirect pages.")]
+    pub skip_seo: bool,
+
+    #[clap(long = "skip-builtin-templates", action = ArgAction::SetTrue, help = "Only uses the templates in the templates folder, rather than filling in missing ones with the built-in templates.")]
+    pub skip_builtin_templates: bool,
+
     #[clap(long = "skip-verification", action = ArgAction::SetTrue, help = "Skips verification of the URLs in the list. Probably unwise!")]
     pub skip_verify: bool,
 
@@ -287,6 +514,202 @@ pub struct ClapSettings {
     pub dry_run: bool,
 }
 
+// Subcommands, for anything other than generating the webring
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    #[clap(
+        about = "Reads another webring's discovery document (/.well-known/webring.json) to validate it or show its members."
+    )]
+    Discover {
+        #[clap(
+            help = "The ring's base URL, or the URL/path of its discovery document."
+        )]
+        location: String,
+
+        #[clap(long = "members", action = ArgAction::SetTrue, help = "Also fetches and lists the ring's members.")]
+        members: bool,
+    },
+
+    #[clap(
+        about = "Creates a new ring in a folder: a config file, an example website list, templates and assets. Asks for anything not given as a flag."
+    )]
+    Init {
+        #[clap(default_value = ".", help = "The folder to create the ring in.")]
+        directory: String,
+
+        #[clap(long = "format", value_parser = ["json", "toml", "yaml", "csv"], help = "Format of the example website list.")]
+        format: Option<String>,
+
+        #[clap(long = "name", help = "The ring's name.")]
+        name: Option<String>,
+
+        #[clap(long = "description", help = "A short description of the ring.")]
+        description: Option<String>,
+
+        #[clap(long = "owner", help = "Who runs the ring.")]
+        owner: Option<String>,
+
+        #[clap(long = "owner-site", help = "The owner's website.")]
+        owner_site: Option<String>,
+
+        #[clap(long = "base-url", help = "The URL the ring will be hosted at.")]
+        base_url: Option<String>,
+
+        #[clap(long = "theme", help = "Built-in theme to start the stylesheet from.")]
+        theme: Option<String>,
+
+        #[clap(long = "no-input", action = ArgAction::SetTrue, help = "Never ask; use defaults for anything not given as a flag.")]
+        no_input: bool,
+
+        #[clap(long = "force", action = ArgAction::SetTrue, help = "Overwrite files which already exist.")]
+        force: bool,
+    },
+
+    #[clap(about = "Adds a site to the website list.")]
+    Add {
+        #[clap(long = "url", help = "The site's URL.")]
+        url: String,
+
+        #[clap(long = "position", help = "Where to put the site in the list, counting from 1. Goes at the end by default.")]
+        position: Option<usize>,
+
+        #[clap(flatten)]
+        fields: MemberFields,
+    },
+
+    #[clap(about = "Removes a site from the website list.")]
+    Remove {
+        #[clap(help = "The site's slug.")]
+        slug: String,
+    },
+
+    #[clap(about = "Changes a site's details in the website list. Pass an empty value to clear a field.")]
+    Edit {
+        #[clap(id = "member", value_name = "SLUG", help = "The site's slug.")]
+        slug: String,
+
+        #[clap(long = "url", help = "The site's new URL.")]
+        url: Option<String>,
+
+        #[clap(flatten)]
+        fields: MemberFields,
+    },
+
+    #[clap(about = "Moves a site to another place in the website list.")]
+    Move {
+        #[clap(help = "The site's slug.")]
+        slug: String,
+
+        #[clap(help = "The site's new position in its list, counting from 1.")]
+        position: usize,
+    },
+
+    #[clap(about = "Writes the website list(s) out as one list in another format.")]
+    Convert {
+        #[clap(help = "Where to write the converted list. Use '-' to print it instead.")]
+        output: String,
+
+        #[clap(long = "format", value_parser = ["json", "toml", "yaml", "csv"], help = "Format to write. Taken from the output's extension by default.")]
+        format: Option<String>,
+
+        #[clap(long = "force", action = ArgAction::SetTrue, help = "Overwrite the output if it already exists.")]
+        force: bool,
+    },
+
+    #[clap(about = "Reads another webring tool's member list, and writes it out as a website list. Lists anything which couldn't be imported.")]
+    Import {
+        #[clap(value_parser = ["onionring", "xxiivv", "opml"], help = "The kind of list: an onionring.js 'variables.js', an XXIIVV-style 'sites.js', or an OPML subscription list.")]
+        from: String,
+
+        #[clap(help = "The list's path or URL.")]
+        source: String,
+
+        #[clap(help = "Where to write the website list. Use '-' to print it instead.")]
+        output: String,
+
+        #[clap(long = "format", value_parser = ["json", "toml", "yaml", "csv"], help = "Format to write. Taken from the output's extension by default.")]
+        format: Option<String>,
+
+        #[clap(long = "force", action = ArgAction::SetTrue, help = "Overwrite the output if it already exists.")]
+        force: bool,
+    },
+
+    #[clap(about = "Goes through the join requests in the pending folder: checks, lints and audits each one, then approves it into the website list or rejects it.")]
+    Review {
+        #[clap(long = "no-audit", action = ArgAction::SetTrue, help = "Don't fetch the sites to check for the ring's links.")]
+        no_audit: bool,
+
+        #[clap(long = "no-input", action = ArgAction::SetTrue, help = "Only report on each request, without asking whether to approve or reject it.")]
+        no_input: bool,
+    },
+
+    #[clap(about = "Audits one site, explaining what was expected and what was found: the next/previous URLs, every link on the page, redirects, status and timing.")]
+    Check {
+        #[clap(help = "The site's slug or URL. A URL which isn't in the list is checked as if it had just been added.")]
+        site: String,
+
+        #[clap(long = "html", help = "Checks a local copy of the page instead of fetching it.")]
+        html: Option<String>,
+    },
+}
+
+/// Details of a site, as given to the `add` and `edit` commands
+#[derive(Args, Debug, Clone, Default)]
+pub struct MemberFields {
+    #[clap(long = "slug", help = "The site's slug. Derived from the URL if left out.")]
+    pub slug: Option<String>,
+
+    #[clap(long = "name", help = "The site's name.")]
+    pub name: Option<String>,
+
+    #[clap(long = "about", help = "A short description of the site.")]
+    pub about: Option<String>,
+
+    #[clap(long = "owner", help = "Who runs the site.")]
+    pub owner: Option<String>,
+
+    #[clap(long = "rss", help = "The site's RSS feed.")]
+    pub rss: Option<String>,
+
+    #[clap(long = "atom", help = "The site's Atom feed.")]
+    pub atom: Option<String>,
+
+    #[clap(long = "gemini-url", help = "The site's Gemini capsule.")]
+    pub gemini_url: Option<String>,
+
+    #[clap(long = "gopher-url", help = "The site's gopher hole.")]
+    pub gopher_url: Option<String>,
+
+    #[clap(long = "status", value_enum, help = "Whether the site is in the ring: 'paused' and 'hidden' sites are left out for now, and 'pending' ones haven't been admitted yet.")]
+    pub status: Option<MemberStatus>,
+
+    #[clap(long = "joined", help = "When the site joined, as YYYY-MM-DD.")]
+    pub joined: Option<String>,
+
+    #[clap(long = "active-from", help = "The first day the site is in the ring, as YYYY-MM-DD.")]
+    pub active_from: Option<String>,
+
+    #[clap(long = "active-until", help = "The last day the site is in the ring, as YYYY-MM-DD.")]
+    pub active_until: Option<String>,
+}
+
+impl Command {
+    /// Whether the command works on this ring, and so needs its config file
+    pub fn needs_config(&self) -> bool {
+        match self {
+            Command::Discover { .. } => false,
+            Command::Init { .. } | Command::Import { .. } => false,
+            Command::Add { .. }
+            | Command::Remove { .. }
+            | Command::Edit { .. }
+            | Command::Move { .. }
+            | Command::Convert { .. }
+            | Command::Review { .. }
+            | Command::Check { .. } => true,
+        }
+    }
+}
+
 pub async fn load_config(config_path: &str) -> Result<Option<ConfigSettings>, Error> {
     // Early return for an empty path
     if config_path.trim().is_empty() {
@@ -309,22 +732,23 @@ pub async fn load_config(config_path: &str) -> Result<Option<ConfigSettings>, Er
     }
 
     // Deserialize based on format
-    // TODO: Add more config file types?
     let ext = file::get_extension_from_path(config_path).unwrap_or_default();
+    let what = |format: &str| format!("{} config '{}'", format, config_path);
     let config: ConfigSettings = match ext.as_str() {
         "json" => serde_json::from_str(&config_content).map_err(|e| {
-            log::error!("Failed to parse JSON config '{}': {}", config_path, e);
-            Error::StringError(format!(
-                "Failed to parse JSON config '{}': {}",
-                config_path, e
-            ))
+            let e = file::json_error(&what("JSON"), &e);
+            log::error!("{}", e);
+            e
         })?,
         "toml" => toml::from_str(&config_content).map_err(|e| {
-            log::error!("Failed to parse TOML config '{}': {}", config_path, e);
-            Error::StringError(format!(
-                "Failed to parse TOML config '{}': {}",
-                config_path, e
-            ))
+            let e = file::toml_error(&what("TOML"), &config_content, &e);
+            log::error!("{}", e);
+            e
+        })?,
+        "yaml" | "yml" => serde_yaml_ng::from_str(&config_content).map_err(|e| {
+            let e = file::yaml_error(&what("YAML"), &e);
+            log::error!("{}", e);
+            e
         })?,
         other => {
             log::error!("Unsupported config file extension: '{}'", other);
@@ -350,6 +774,26 @@ async fn merge_configs(cli_args: ClapSettings, config: self::ConfigSettings) ->
     }
     final_settings.filepath_list = de_dupe.into_iter().collect();
 
+    final_settings.blocklists = {
+        let mut v = Vec::new();
+        v.extend(cli_args.blocklists);
+        if let Some(c) = config.blocklists {
+            v.extend(c);
+        }
+        v
+    };
+    final_settings.blocklist_drop =
+        cli_args.blocklist_drop || config.blocklist_drop.unwrap_or(final_settings.blocklist_drop);
+
+    final_settings.private_fields = {
+        let mut v = Vec::new();
+        v.extend(cli_args.private_fields);
+        if let Some(c) = config.private_fields {
+            v.extend(c);
+        }
+        v
+    };
+
     final_settings.json_lists = {
         let mut v = Vec::new();
         v.extend(cli_args.json_list);
@@ -368,6 +812,15 @@ async fn merge_configs(cli_args: ClapSettings, config: self::ConfigSettings) ->
         v
     };
 
+    final_settings.yaml_lists = {
+        let mut v = Vec::new();
+        v.extend(cli_args.yaml_list);
+        if let Some(c) = config.yaml_list {
+            v.extend(c);
+        }
+        v
+    };
+
     final_settings.ring_name = cli_args
         .ring_name
         .or(config.ring_name)
@@ -388,6 +841,22 @@ async fn merge_configs(cli_args: ClapSettings, config: self::ConfigSettings) ->
         .filename_template_redirect
         .or(config.filename_template_redirect)
         .unwrap_or(final_settings.filename_template_redirect);
+    final_settings.filename_template_join = cli_args
+        .filename_template_join
+        .or(config.filename_template_join)
+        .unwrap_or(final_settings.filename_template_join);
+    final_settings.filename_template_member = cli_args
+        .filename_template_member
+        .or(config.filename_template_member)
+        .unwrap_or(final_settings.filename_template_member);
+    final_settings.filename_template_random = cli_args
+        .filename_template_random
+        .or(config.filename_template_random)
+        .unwrap_or(final_settings.filename_template_random);
+    final_settings.filename_template_universal = cli_args
+        .filename_template_universal
+        .or(config.filename_template_universal)
+        .unwrap_or(final_settings.filename_template_universal);
     final_settings.path_output = cli_args
         .path_output
         .or(config.path_output)
@@ -400,10 +869,34 @@ async fn merge_configs(cli_args: ClapSettings, config: self::ConfigSettings) ->
         .path_templates
         .or(config.path_templates)
         .unwrap_or(final_settings.path_templates);
+    final_settings.path_pending = cli_args
+        .path_pending
+        .or(config.path_pending)
+        .unwrap_or(final_settings.path_pending);
+    final_settings.theme = cli_args
+        .theme
+        .or(config.theme)
+        .unwrap_or(final_settings.theme);
     final_settings.base_url = cli_args
         .base_url
         .or(config.base_url)
         .unwrap_or(final_settings.base_url);
+    final_settings.gemini_base_url = cli_args
+        .gemini_base_url
+        .or(config.gemini_base_url)
+        .unwrap_or(final_settings.gemini_base_url);
+    final_settings.path_output_gemini = cli_args
+        .path_output_gemini
+        .or(config.path_output_gemini)
+        .unwrap_or(final_settings.path_output_gemini);
+    final_settings.gopher_base_url = cli_args
+        .gopher_base_url
+        .or(config.gopher_base_url)
+        .unwrap_or(final_settings.gopher_base_url);
+    final_settings.path_output_gopher = cli_args
+        .path_output_gopher
+        .or(config.path_output_gopher)
+        .unwrap_or(final_settings.path_output_gopher);
 
     final_settings.next_url_text = cli_args
         .next_url_text
@@ -415,6 +908,20 @@ async fn merge_configs(cli_args: ClapSettings, config: self::ConfigSettings) ->
         .or(config.prev_url_text)
         .unwrap_or(final_settings.prev_url_text);
 
+    final_settings.random_url_text = cli_args
+        .random_url_text
+        .or(config.random_url_text)
+        .unwrap_or(final_settings.random_url_text);
+
+    final_settings.random_exclude = {
+        let mut v = Vec::new();
+        v.extend(cli_args.random_exclude);
+        if let Some(c) = config.random_exclude {
+            v.extend(c);
+        }
+        v
+    };
+
     final_settings.client_header = cli_args
         .client_header
         .or(config.client_header)
@@ -434,14 +941,55 @@ async fn merge_configs(cli_args: ClapSettings, config: self::ConfigSettings) ->
         .unwrap_or(final_settings.audit_retries_max);
 
     final_settings.audit = cli_args.audit || config.audit.unwrap_or(final_settings.audit);
+    final_settings.verification_secret = cli_args
+        .verification_secret
+        .or(config.verification_secret)
+        .unwrap_or(final_settings.verification_secret);
+    final_settings.require_verified = cli_args.require_verified
+        || config
+            .require_verified
+            .unwrap_or(final_settings.require_verified);
     final_settings.no_slug = cli_args.no_slug || config.no_slug.unwrap_or(final_settings.no_slug);
+    final_settings.random_exclude_neighbors = cli_args.random_exclude_neighbors
+        || config
+            .random_exclude_neighbors
+            .unwrap_or(final_settings.random_exclude_neighbors);
+    final_settings.universal_links = cli_args.universal_links
+        || config
+            .universal_links
+            .unwrap_or(final_settings.universal_links);
+    final_settings.onionring =
+        cli_args.onionring || config.onionring.unwrap_or(final_settings.onionring);
+    final_settings.onionring_id = cli_args
+        .onionring_id
+        .or(config.onionring_id)
+        .unwrap_or(final_settings.onionring_id);
+    final_settings.server_configs = {
+        let mut v = Vec::new();
+        v.extend(cli_args.server_configs);
+        if let Some(c) = config.server_configs {
+            v.extend(c);
+        }
+        v
+    };
+    final_settings.skip_redirect_pages = cli_args.skip_redirect_pages
+        || config
+            .skip_redirect_pages
+            .unwrap_or(final_settings.skip_redirect_pages);
     final_settings.shuffle = cli_args.shuffle || config.shuffle.unwrap_or(final_settings.shuffle);
     //final_settings.verbose = cli_args.verbose || config.verbose.unwrap_or(final_settings.verbose);
     final_settings.skip_minify =
         cli_args.skip_minify || config.skip_minify.unwrap_or(final_settings.skip_minify);
+    final_settings.skip_seo =
+        cli_args.skip_seo || config.skip_seo.unwrap_or(final_settings.skip_seo);
+    final_settings.skip_builtin_templates = cli_args.skip_builtin_templates
+        || config
+            .skip_builtin_templates
+            .unwrap_or(final_settings.skip_builtin_templates);
     final_settings.skip_verify =
         cli_args.skip_verify || config.skip_verify.unwrap_or(final_settings.skip_verify);
     final_settings.dry_run = cli_args.dry_run || config.dry_run.unwrap_or(final_settings.dry_run);
+    final_settings.command = cli_args.command;
 
     // HACK: just set the config file value, then CLI value, directly
     std::env::set_var("RUST_LOG", "error"); // Default to only showing errors
@@ -480,6 +1028,12 @@ pub async fn parse_args() -> Result<AppSettings, Error> {
         clap_args.filepath_config.clone()
     };
 
+    // Some commands don't operate on this ring, so they don't need its config
+    let config_path = match clap_args.command {
+        Some(ref command) if !command.needs_config() => None,
+        _ => config_path,
+    };
+
     // Check if a config file path is provided, and it's not empty
     let config_args = match load_config(config_path.as_deref().unwrap_or("")).await {
         Ok(Some(cfg)) => cfg,
@@ -493,5 +1047,17 @@ pub async fn parse_args() -> Result<AppSettings, Error> {
         }
     };
 
-    Ok(merge_configs(clap_args, config_args).await)
+    let settings = merge_configs(clap_args, config_args).await;
+    // Catch typos in server config names before anything gets built
+    for name in &settings.server_configs {
+        crate::gen::server::ServerKind::from_name(name)?;
+    }
+    // The onionring embed is only recognised as this ring's by where its scripts come from
+    if settings.onionring && settings.base_url.trim().is_empty() {
+        return Err(Error::StringError(
+            "onionring needs base_url (--url), the address the embed's scripts are loaded from".to_string(),
+        ));
+    }
+
+    Ok(settings)
 }
@@ -0,0 +1,294 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::file::acquire_file_data;
+use crate::gen::json::MEMBERS_JSON_PATH;
+use crate::website::{Provenance, Website};
+
+/// Where rings publish their discovery document, relative to their base URL
+pub const WELL_KNOWN_PATH: &str = ".well-known/webring.json";
+
+/// Placeholder for a member's slug in the URL patterns
+pub const SLUG_PLACEHOLDER: &str = "{slug}";
+
+/// Website list entries with this prefix import another ring's members, e.g. `ring:https://other.tld`
+pub const RING_MEMBERS_PREFIX: &str = "ring:";
+
+/// Website list entries with this prefix add another ring as a single member, linking to its hub
+pub const RING_HUB_PREFIX: &str = "ring-hub:";
+
+/// Describes a webring, so tools can find its hub, members and redirect URLs
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct RingDiscovery {
+    pub name: String,
+    pub description: Option<String>,
+    pub owner: Option<String>,
+    pub owner_site: Option<String>,
+    pub hub: String,
+    pub next_url: String,
+    pub previous_url: String,
+    pub random_url: Option<String>,
+    pub member_count: usize,
+    pub members: String,
+    pub generator: Option<String>,
+}
+
+impl RingDiscovery {
+    /// Lists anything about the document which tools won't be able to use
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.name.trim().is_empty() {
+            problems.push("The ring has no name.".to_string());
+        }
+        if url::Url::parse(&self.hub).is_err() {
+            problems.push(format!("Hub URL isn't absolute: '{}'", self.hub));
+        }
+        for (label, pattern) in [
+            ("Next", Some(&self.next_url)),
+            ("Previous", Some(&self.previous_url)),
+            ("Random", self.random_url.as_ref()),
+        ] {
+            if let Some(pattern) = pattern {
+                if !pattern.contains(SLUG_PLACEHOLDER) {
+                    problems.push(format!(
+                        "{} URL pattern doesn't contain {}: '{}'",
+                        label, SLUG_PLACEHOLDER, pattern
+                    ));
+                }
+            }
+        }
+        if self.members.trim().is_empty() {
+            problems.push("No member list location given.".to_string());
+        }
+        problems
+    }
+}
+
+/// Turns a ring's base URL into the URL of its discovery document, unless it already points to a document
+pub fn discovery_location(location: &str) -> String {
+    if location.ends_with(".json") {
+        location.to_string()
+    } else {
+        format!("{}/{}", location.trim_end_matches('/'), WELL_KNOWN_PATH)
+    }
+}
+
+/// Loads & parses another ring's discovery document
+pub async fn fetch_discovery(location: &str) -> Result<(String, RingDiscovery), Error> {
+    let document_location = discovery_location(location);
+    let data = acquire_file_data(&document_location).await?;
+    let document = serde_json::from_str(&data).map_err(|e| {
+        Error::StringError(format!(
+            "Failed to parse discovery document '{}': {}",
+            document_location, e
+        ))
+    })?;
+    Ok((document_location, document))
+}
+
+/// Loads the member list a discovery document points to (which may be relative to the document)
+pub async fn fetch_members(
+    document_location: &str,
+    document: &RingDiscovery,
+) -> Result<Vec<Website>, Error> {
+    let members_location = match url::Url::parse(document_location) {
+        Ok(base) => base
+            .join(&document.members)
+            .map(|url| url.to_string())
+            .unwrap_or_else(|_| document.members.clone()),
+        Err(_) => document.members.clone(),
+    };
+    let data = acquire_file_data(&members_location).await?;
+    serde_json::from_str(&data).map_err(|e| {
+        Error::StringError(format!(
+            "Failed to parse member list '{}': {}",
+            members_location, e
+        ))
+    })
+}
+
+/// Imports another ringfairy ring, given its base URL, discovery document or member list.
+/// Either its members are imported (with slugs prefixed by the ring's name, to avoid clashes),
+/// or the whole ring becomes a single site which links to its hub.
+pub async fn import_ring(location: &str, as_single_site: bool) -> Result<Vec<Website>, Error> {
+    // The member list alone doesn't say much about the ring, so look for its discovery document
+    let location = location
+        .strip_suffix(MEMBERS_JSON_PATH)
+        .unwrap_or(location);
+    let (document_location, document) = fetch_discovery(location).await?;
+    let namespace = document
+        .name
+        .replace(|c: char| !c.is_alphanumeric(), "")
+        .to_lowercase();
+
+    if as_single_site {
+        log::info!("Adding ring '{}' as a single site", document.name);
+        return Ok(vec![Website {
+            slug: namespace.clone(),
+            name: Some(document.name.clone()),
+            about: document.description.clone(),
+            url: document.hub.clone(),
+            owner: document.owner.clone(),
+            provenance: Some(Provenance {
+                ring_name: document.name.clone(),
+                ring_hub: document.hub.clone(),
+                slug: namespace,
+                is_ring: true,
+            }),
+            ..Default::default()
+        }]);
+    }
+
+    let members = fetch_members(&document_location, &document).await?;
+    log::info!(
+        "Importing {} sites from ring '{}'",
+        members.len(),
+        document.name
+    );
+    Ok(members
+        .into_iter()
+        .map(|mut website| {
+            website.provenance = Some(Provenance {
+                ring_name: document.name.clone(),
+                ring_hub: document.hub.clone(),
+                slug: website.slug.clone(),
+                is_ring: false,
+            });
+            website.slug = format!("{}-{}", namespace, website.slug);
+            website
+        })
+        .collect())
+}
+
+/// Entry point for the `discover` command
+pub async fn discover_ring(location: &str, show_members: bool) -> Result<(), Error> {
+    let (document_location, document) = fetch_discovery(location).await?;
+
+    println!("{} ({})", document.name, document.hub);
+    if let Some(description) = &document.description {
+        println!("{}", description);
+    }
+    if let Some(owner) = &document.owner {
+        println!("Maintained by {}", owner);
+    }
+    println!("Members: {}", document.member_count);
+
+    let mut problems = document.problems();
+
+    if show_members {
+        let members = fetch_members(&document_location, &document).await?;
+        println!();
+        for website in &members {
+            println!(
+                "{}\t{}\t{}",
+                website.slug,
+                website.url,
+                website.name.as_deref().unwrap_or("")
+            );
+        }
+        if members.len() != document.member_count {
+            problems.push(format!(
+                "Document claims {} members, but the list has {}.",
+                document.member_count,
+                members.len()
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        println!("\nDiscovery document looks valid.");
+        Ok(())
+    } else {
+        for problem in &problems {
+            println!("Problem: {}", problem);
+        }
+        Err(Error::StringError(format!(
+            "Discovery document '{}' has {} problem(s).",
+            document_location,
+            problems.len()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_discovery_location() {
+        assert_eq!(
+            discovery_location("https://ring.tld/"),
+            "https://ring.tld/.well-known/webring.json"
+        );
+        assert_eq!(
+            discovery_location("https://ring.tld/ring.json"),
+            "https://ring.tld/ring.json"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_discovery_and_members() {
+        let mut server = mockito::Server::new_async().await;
+        let document = server
+            .mock("GET", "/.well-known/webring.json")
+            .with_status(200)
+            .with_body(
+                r#"{"name": "Other Ring", "hub": "https://other.tld/",
+                "next_url": "https://other.tld/{slug}/next", "previous_url": "https://other.tld/prev",
+                "member_count": 1, "members": "/members.json"}"#,
+            )
+            .create();
+        let members = server
+            .mock("GET", "/members.json")
+            .with_status(200)
+            .with_body(r#"[{"slug": "a", "url": "https://a.tld"}]"#)
+            .create();
+
+        let (location, ring) = fetch_discovery(&server.url()).await.unwrap();
+        assert_eq!(ring.name, "Other Ring");
+        let problems = ring.problems();
+        assert_eq!(problems.len(), 1, "Previous URL has no slug: {:?}", problems);
+
+        let websites = fetch_members(&location, &ring).await.unwrap();
+        assert_eq!(websites.len(), 1);
+        assert_eq!(websites[0].slug, "a");
+
+        document.assert_async().await;
+        members.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_import_ring() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/.well-known/webring.json")
+            .with_status(200)
+            .with_body(
+                r#"{"name": "Other Ring!", "hub": "https://other.tld/",
+                "next_url": "https://other.tld/{slug}/next", "previous_url": "https://other.tld/{slug}/prev",
+                "member_count": 2, "members": "/members.json"}"#,
+            )
+            .expect(2)
+            .create();
+        server
+            .mock("GET", "/members.json")
+            .with_status(200)
+            .with_body(r#"[{"slug": "a", "url": "https://a.tld"}, {"slug": "b", "url": "https://b.tld"}]"#)
+            .create();
+
+        let members = import_ring(&format!("{}/members.json", server.url()), false)
+            .await
+            .unwrap();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].slug, "otherring-a");
+        let provenance = members[0].provenance.as_ref().unwrap();
+        assert_eq!(provenance.slug, "a");
+        assert!(!provenance.is_ring);
+
+        let hub = import_ring(&server.url(), true).await.unwrap();
+        assert_eq!(hub.len(), 1);
+        assert_eq!(hub[0].slug, "otherring");
+        assert_eq!(hub[0].url, "https://other.tld/");
+        assert!(hub[0].provenance.as_ref().unwrap().is_ring);
+    }
+}
@@ -1,58 +1,807 @@
 use crate::cli::AppSettings;
+use crate::discover::{import_ring, RING_HUB_PREFIX, RING_MEMBERS_PREFIX};
 use crate::error::Error;
 use crate::http::download_file;
-use crate::website::Website;
+use crate::website::{MemberStatus, Website, WebsitesTomlFormat};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// The list formats which can be both read and written
+pub const LIST_FORMATS: &[&str] = &["json", "toml", "yaml", "csv"];
+
+/// A website as one CSV row. CSV can't nest, so `provenance`, `misc` and `private` are kept as JSON text.
+#[derive(Deserialize, Serialize)]
+struct CsvWebsite {
+    slug: String,
+    name: Option<String>,
+    about: Option<String>,
+    url: String,
+    rss: Option<String>,
+    atom: Option<String>,
+    owner: Option<String>,
+    gemini_url: Option<String>,
+    gopher_url: Option<String>,
+    status: Option<MemberStatus>,
+    joined: Option<NaiveDate>,
+    active_from: Option<NaiveDate>,
+    active_until: Option<NaiveDate>,
+    provenance: Option<String>,
+    verified: Option<bool>,
+    misc: Option<String>,
+    private: Option<String>,
+}
+
+impl CsvWebsite {
+    fn from_website(website: &Website) -> Result<Self, Error> {
+        Ok(CsvWebsite {
+            slug: website.slug.clone(),
+            name: website.name.clone(),
+            about: website.about.clone(),
+            url: website.url.clone(),
+            rss: website.rss.clone(),
+            atom: website.atom.clone(),
+            owner: website.owner.clone(),
+            gemini_url: website.gemini_url.clone(),
+            gopher_url: website.gopher_url.clone(),
+            status: website.status,
+            joined: website.joined,
+            active_from: website.active_from,
+            active_until: website.active_until,
+            provenance: website.provenance.as_ref().map(serde_json::to_string).transpose()?,
+            verified: website.verified,
+            misc: website.misc.as_ref().map(serde_json::to_string).transpose()?,
+            private: website.private.as_ref().map(serde_json::to_string).transpose()?,
+        })
+    }
+
+    fn into_website(self) -> Result<Website, Error> {
+        Ok(Website {
+            slug: self.slug,
+            name: self.name,
+            about: self.about,
+            url: self.url,
+            rss: self.rss,
+            atom: self.atom,
+            owner: self.owner,
+            gemini_url: self.gemini_url,
+            gopher_url: self.gopher_url,
+            status: self.status,
+            joined: self.joined,
+            active_from: self.active_from,
+            active_until: self.active_until,
+            // Written out for reference, but only ring imports set it
+            provenance: None,
+            // Only the audit decides this
+            verified: None,
+            // Hand-written CSV might have plain text here, rather than JSON
+            misc: self.misc.map(|misc| {
+                serde_json::from_str(&misc).unwrap_or(serde_json::Value::String(misc))
+            }),
+            private: self.private.map(|private| {
+                serde_json::from_str(&private).unwrap_or(serde_json::Value::String(private))
+            }),
+        })
+    }
+}
 
 fn parse_csv_websites(csv_data: &str) -> Result<Vec<Website>, Error> {
     let mut rdr = csv::Reader::from_reader(csv_data.as_bytes());
     let mut websites = Vec::new();
     for result in rdr.deserialize() {
-        let website: Website = result
+        let row: CsvWebsite = result
             .map_err(|e| Error::StringError(format!("Failed to parse CSV row: {}", e)))?;
-        websites.push(website);
+        websites.push(row.into_website()?);
     }
     Ok(websites)
 }
 
+/// TOML lists are normally a `[[websites]]` table per site, but a bare array of inline tables is fine too
+fn parse_toml_websites(toml_data: &str, what: &str) -> Result<Vec<Website>, Error> {
+    let trimmed = toml_data.trim_start();
+    if trimmed.starts_with('[') && !trimmed.starts_with("[[") {
+        const PREFIX: &str = "websites = ";
+        let wrapped = format!("{}{}", PREFIX, trimmed);
+        return toml::from_str::<WebsitesTomlFormat>(&wrapped)
+            .map(|list| list.websites)
+            .map_err(|e| {
+                // Point at the line & column of the original text, not the wrapped one
+                let location = e.span().map(|span| match line_column(&wrapped, span.start) {
+                    (1, column) => (1, column.saturating_sub(PREFIX.len()).max(1)),
+                    location => location,
+                });
+                parse_error(what, e.message(), location)
+            });
+    }
+    toml::from_str::<WebsitesTomlFormat>(toml_data)
+        .map(|list| list.websites)
+        .map_err(|e| toml_error(what, toml_data, &e))
+}
+
+/// YAML lists can be a bare sequence of sites, or a mapping with a `websites` key
+fn parse_yaml_websites(yaml_data: &str, what: &str) -> Result<Vec<Website>, Error> {
+    let document: serde_yaml_ng::Value = serde_yaml_ng::from_str(yaml_data).map_err(|e| yaml_error(what, &e))?;
+    if document.get("websites").is_some() {
+        serde_yaml_ng::from_str::<WebsitesTomlFormat>(yaml_data).map(|list| list.websites)
+    } else {
+        serde_yaml_ng::from_str::<Vec<Website>>(yaml_data)
+    }
+    .map_err(|e| yaml_error(what, &e))
+}
+
+/// Turns a byte offset into a line & column, both counting from 1
+fn line_column(data: &str, offset: usize) -> (usize, usize) {
+    let before = &data[..offset.min(data.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    (line, column)
+}
+
+/// Builds a parse error for `what` (e.g. "JSON file 'websites.json'"), pointing at the line & column if known
+fn parse_error(what: &str, message: &str, location: Option<(usize, usize)>) -> Error {
+    match location {
+        Some((line, column)) => Error::StringError(format!(
+            "Failed to parse {} at line {}, column {}: {}",
+            what, line, column, message
+        )),
+        None => Error::StringError(format!("Failed to parse {}: {}", what, message)),
+    }
+}
+
+/// Drops the " at line X column Y" which serde_json & serde_yaml_ng tack onto their messages, since parse_error() adds its own
+fn strip_location_suffix(message: String, line: usize, column: usize) -> String {
+    let suffix = format!(" at line {} column {}", line, column);
+    message.strip_suffix(&suffix).map(str::to_string).unwrap_or(message)
+}
+
+pub fn json_error(what: &str, e: &serde_json::Error) -> Error {
+    let message = strip_location_suffix(e.to_string(), e.line(), e.column());
+    parse_error(what, &message, Some((e.line(), e.column())).filter(|(line, _)| *line > 0))
+}
+
+pub fn toml_error(what: &str, data: &str, e: &toml::de::Error) -> Error {
+    let location = e.span().map(|span| line_column(data, span.start));
+    parse_error(what, e.message(), location)
+}
+
+pub fn yaml_error(what: &str, e: &serde_yaml_ng::Error) -> Error {
+    match e.location() {
+        Some(location) => {
+            let message = strip_location_suffix(e.to_string(), location.line(), location.column());
+            parse_error(what, &message, Some((location.line(), location.column())))
+        }
+        None => parse_error(what, &e.to_string(), None),
+    }
+}
+
+/// Turns a list of websites into plain values, leaving out empty fields
+fn websites_without_nulls(websites: &[Website]) -> Result<serde_json::Value, Error> {
+    let mut value = serde_json::to_value(websites)?;
+    if let Some(sites) = value.as_array_mut() {
+        for site in sites.iter_mut().filter_map(|site| site.as_object_mut()) {
+            site.retain(|_, field| !field.is_null());
+        }
+    }
+    Ok(value)
+}
+
+/// Turns a list of websites into the contents of a list file, in the given format ("json", "toml", "yaml" or "csv").
+/// Empty fields are left out, so the result reads like a hand-written list.
+pub fn serialize_website_list(websites: &[Website], format: &str) -> Result<String, Error> {
+    match format {
+        "json" => Ok(serde_json::to_string_pretty(&websites_without_nulls(websites)?)? + "\n"),
+        "yaml" | "yml" => serde_yaml_ng::to_string(&websites_without_nulls(websites)?)
+            .map_err(|e| Error::StringError(format!("Failed to write YAML list: {}", e))),
+        "toml" => toml::to_string(&WebsitesTomlFormat {
+            websites: websites.to_vec(),
+        })
+        .map_err(|e| Error::StringError(format!("Failed to write TOML list: {}", e))),
+        "csv" => {
+            let mut wtr = csv::Writer::from_writer(Vec::new());
+            for website in websites {
+                wtr.serialize(CsvWebsite::from_website(website)?)
+                    .map_err(|e| Error::StringError(format!("Failed to write CSV row for '{}': {}", website.slug, e)))?;
+            }
+            let data = wtr
+                .into_inner()
+                .map_err(|e| Error::StringError(format!("Failed to write CSV list: {}", e)))?;
+            Ok(String::from_utf8(data)?)
+        }
+        other => Err(Error::StringError(format!("Unsupported file format '{}'", other))),
+    }
+}
+
 /// Loads the given file(s) with acquire_file_data(), returns a vec of Websites for each site in the file
 pub async fn parse_website_list(settings: &AppSettings) -> Result<Vec<Website>, Error> {
     let mut all_websites = Vec::new();
 
     // JSON literals
     for json in &settings.json_lists {
-        let mut list: Vec<Website> = serde_json::from_str(json)
-            .map_err(|e| Error::StringError(format!("Failed to parse JSON literal: {e}")))?;
-        all_websites.append(&mut list);
+        all_websites.append(&mut parse_website_data(json, "json", "JSON literal")?);
     }
 
     // TOML literals
     for toml in &settings.toml_lists {
-        let mut list: Vec<Website> = toml::from_str(toml)
-            .map_err(|e| Error::StringError(format!("Failed to parse TOML literal: {e}")))?;
-        all_websites.append(&mut list);
+        all_websites.append(&mut parse_website_data(toml, "toml", "TOML literal")?);
+    }
+
+    // YAML literals
+    for yaml in &settings.yaml_lists {
+        all_websites.append(&mut parse_website_data(yaml, "yaml", "YAML literal")?);
     }
 
     // Load file(s)
     for path in &settings.filepath_list {
+        // Other rings, federated into this one
+        if let Some(location) = path.strip_prefix(RING_MEMBERS_PREFIX) {
+            all_websites.append(&mut import_ring(location, false).await?);
+            continue;
+        }
+        if let Some(location) = path.strip_prefix(RING_HUB_PREFIX) {
+            all_websites.append(&mut import_ring(location, true).await?);
+            continue;
+        }
+
+        // A folder with a file per member
+        if Path::new(path).is_dir() {
+            all_websites.append(&mut parse_website_directory(path)?);
+            continue;
+        }
+
         let file_data = acquire_file_data(path).await?;
-        let ext = get_extension_from_path(path).unwrap_or_else(|| "json".into());
-        let mut list = match ext.as_str() {
-            "json" => serde_json::from_str::<Vec<Website>>(&file_data)
-                .map_err(|e| Error::StringError(format!("Failed to parse JSON file '{}': {}", path, e)))?,
-            "toml" => toml::from_str::<Vec<Website>>(&file_data)
-                .map_err(|e| Error::StringError(format!("Failed to parse TOML file '{}': {}", path, e)))?,
-            "csv" => parse_csv_websites(&file_data)
-                .map_err(|e| Error::StringError(format!("Failed to parse CSV file '{}': {}", path, e)))?,
-            other => return Err(Error::StringError(format!("Unsupported file format '{}'", other))),
-        };
+        let mut list = parse_website_file(path, &file_data)?;
         all_websites.append(&mut list);
     }
 
     Ok(all_websites)
 }
 
+/// Parses the contents of one list file, picking the format from its extension (JSON if there isn't one)
+pub fn parse_website_file(path: &str, file_data: &str) -> Result<Vec<Website>, Error> {
+    let ext = get_extension_from_path(path).unwrap_or_else(|| "json".into());
+    let what = format!("{} file '{}'", ext.to_uppercase(), path);
+    parse_website_data(file_data, &ext, &what)
+}
+
+/// Parses a website list in the given format. `what` describes where it came from, for error messages.
+pub fn parse_website_data(data: &str, format: &str, what: &str) -> Result<Vec<Website>, Error> {
+    match format {
+        "json" => serde_json::from_str::<Vec<Website>>(data).map_err(|e| json_error(what, &e)),
+        "toml" => parse_toml_websites(data, what),
+        "yaml" | "yml" => parse_yaml_websites(data, what),
+        "csv" => parse_csv_websites(data)
+            .map_err(|e| Error::StringError(format!("Failed to parse {}: {}", what, e))),
+        other => Err(Error::StringError(format!("Unsupported file format '{}'", other))),
+    }
+}
+
+/// In a directory of member files, this file sets the order of the members, one slug per line
+pub const MEMBER_ORDER_FILE: &str = "order.txt";
+
+/// Splits a Markdown file's front matter (`---` for YAML, `+++` for TOML) from its body.
+/// The opening delimiter is replaced with an empty line, so parse errors still point at the right line.
+fn split_front_matter(markdown: &str) -> Option<(&'static str, String, &str)> {
+    let markdown = markdown.trim_start_matches('\u{feff}');
+    let (format, delimiter) = match markdown.get(..3) {
+        Some("---") => ("yaml", "---"),
+        Some("+++") => ("toml", "+++"),
+        _ => return None,
+    };
+    let rest = markdown[3..].strip_prefix('\n').or_else(|| markdown[3..].strip_prefix("\r\n"))?;
+    let end = rest
+        .match_indices(delimiter)
+        .map(|(index, _)| index)
+        .find(|index| *index == 0 || rest[..*index].ends_with('\n'))?;
+    let body = rest[end + 3..].trim_start_matches(['\r', '\n']);
+    Some((format, format!("\n{}", &rest[..end]), body))
+}
+
+/// Parses one member file. The file name (minus extension) is the default slug, and a Markdown body is the default `about`.
+/// Returns the member with its `order` field, if any, or None for files which aren't member files.
+pub fn parse_member_file(
+    data: &str,
+    ext: &str,
+    default_slug: &str,
+    what: &str,
+) -> Result<Option<(Option<i64>, Website)>, Error> {
+    let (mut fields, body): (serde_json::Value, Option<&str>) = match ext {
+        "json" => (serde_json::from_str(data).map_err(|e| json_error(what, &e))?, None),
+        "toml" => (toml::from_str(data).map_err(|e| toml_error(what, data, &e))?, None),
+        "yaml" | "yml" => (serde_yaml_ng::from_str(data).map_err(|e| yaml_error(what, &e))?, None),
+        "md" | "markdown" => {
+            let (format, front_matter, body) = split_front_matter(data).ok_or_else(|| {
+                Error::StringError(format!("Failed to parse {}: no front matter ('---' or '+++') found", what))
+            })?;
+            let fields = match format {
+                "toml" => toml::from_str(&front_matter).map_err(|e| toml_error(what, &front_matter, &e))?,
+                _ => serde_yaml_ng::from_str(&front_matter).map_err(|e| yaml_error(what, &e))?,
+            };
+            (fields, Some(body.trim()))
+        }
+        _ => return Ok(None),
+    };
+
+    let object = fields
+        .as_object_mut()
+        .ok_or_else(|| Error::StringError(format!("Failed to parse {}: expected a table of fields", what)))?;
+    let order = object
+        .remove("order")
+        .map(|order| {
+            order.as_i64().ok_or_else(|| {
+                Error::StringError(format!("Failed to parse {}: 'order' must be a whole number, not {}", what, order))
+            })
+        })
+        .transpose()?;
+    if object.get("slug").and_then(|slug| slug.as_str()).is_none_or(str::is_empty) {
+        object.insert("slug".into(), default_slug.into());
+    }
+    if let Some(body) = body.filter(|body| !body.is_empty()) {
+        object.entry("about").or_insert_with(|| body.into());
+    }
+
+    let website = serde_json::from_value(fields)
+        .map_err(|e| Error::StringError(format!("Failed to parse {}: {}", what, e)))?;
+    Ok(Some((order, website)))
+}
+
+/// Turns one website into the contents of a member file, in the given format ("json", "toml", "yaml" or "md").
+/// Markdown files get YAML front matter. Empty fields are left out.
+pub fn serialize_member_file(website: &Website, ext: &str) -> Result<String, Error> {
+    let fields = websites_without_nulls(std::slice::from_ref(website))?;
+    let fields = fields[0].clone();
+    let yaml = || {
+        serde_yaml_ng::to_string(&fields).map_err(|e| Error::StringError(format!("Failed to write YAML member file: {}", e)))
+    };
+    match ext {
+        "json" => Ok(serde_json::to_string_pretty(&fields)? + "\n"),
+        "toml" => toml::to_string(&fields)
+            .map_err(|e| Error::StringError(format!("Failed to write TOML member file: {}", e))),
+        "yaml" | "yml" => yaml(),
+        "md" | "markdown" => Ok(format!("---\n{}---\n", yaml()?)),
+        other => Err(Error::StringError(format!("Unsupported member file format '{}'", other))),
+    }
+}
+
+/// Lists the files in a folder of member files, by file name. The order file, and anything starting with '.' or '_', is left out.
+pub fn member_file_paths(dir: &str) -> Result<Vec<PathBuf>, Error> {
+    let mut entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut paths = Vec::new();
+    for entry in entries {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if entry.file_type()?.is_file()
+            && !file_name.starts_with('.')
+            && !file_name.starts_with('_')
+            && file_name != MEMBER_ORDER_FILE
+        {
+            paths.push(entry.path());
+        }
+    }
+    Ok(paths)
+}
+
+/// Reads a directory with one file per member (`<slug>.toml`, `.json`, `.yaml` or `.md` with front matter).
+/// Members are ordered by the order file if there is one, then by their `order` field, then by file name.
+/// Files starting with '.' or '_' are ignored.
+pub fn parse_website_directory(dir: &str) -> Result<Vec<Website>, Error> {
+    let mut members = Vec::new();
+    for path in member_file_paths(dir)? {
+        let path_str = path.display().to_string();
+        let (Some(ext), Some(stem)) = (
+            get_extension_from_path(&path_str),
+            path.file_stem().and_then(|stem| stem.to_str()),
+        ) else {
+            continue;
+        };
+
+        let what = format!("member file '{}'", path_str);
+        match parse_member_file(&fs::read_to_string(&path)?, &ext, stem, &what)? {
+            Some(member) => members.push(member),
+            None => log::warn!("Skipping {}: not a JSON, TOML, YAML or Markdown file", what),
+        }
+    }
+
+    // Members with an `order` come first; sorting is stable, so the rest stay in file name order
+    members.sort_by_key(|(order, _)| order.unwrap_or(i64::MAX));
+    let mut members: Vec<Website> = members.into_iter().map(|(_, website)| website).collect();
+
+    let order_path = Path::new(dir).join(MEMBER_ORDER_FILE);
+    if order_path.is_file() {
+        let mut ordered = Vec::with_capacity(members.len());
+        for slug in fs::read_to_string(&order_path)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        {
+            match members.iter().position(|website| website.slug == slug) {
+                Some(index) => ordered.push(members.remove(index)),
+                None => log::warn!("'{}' lists '{}', but there's no member with that slug", order_path.display(), slug),
+            }
+        }
+        for website in &members {
+            log::warn!("'{}' isn't listed in '{}'; adding it at the end", website.slug, order_path.display());
+        }
+        ordered.append(&mut members);
+        members = ordered;
+    }
+
+    Ok(members)
+}
+
+/// Reads the website list(s) from the settings, and writes them out as one list in the given format
+/// (or the one matching the output's extension). An output of '-' prints the list instead.
+pub async fn convert_website_list(
+    settings: &AppSettings,
+    output: &str,
+    format: Option<&str>,
+    force: bool,
+) -> Result<(), Error> {
+    let websites = parse_website_list(settings).await?;
+    write_website_list(&websites, output, format, force)
+}
+
+/// Writes a website list in the given format (or the one matching the output's extension). An output of '-' prints it instead.
+pub fn write_website_list(
+    websites: &[Website],
+    output: &str,
+    format: Option<&str>,
+    force: bool,
+) -> Result<(), Error> {
+    let format = format
+        .map(|format| format.to_lowercase())
+        .or_else(|| get_extension_from_path(output))
+        .map(|format| if format == "yml" { "yaml".into() } else { format })
+        .ok_or_else(|| Error::StringError(format!("Can't tell which format to write '{}' in; use --format", output)))?;
+    if !LIST_FORMATS.contains(&format.as_str()) {
+        return Err(Error::StringError(format!(
+            "Unsupported file format '{}' (available: {})",
+            format,
+            LIST_FORMATS.join(", ")
+        )));
+    }
+
+    let content = serialize_website_list(websites, &format)?;
+
+    if output == "-" {
+        print!("{}", content);
+        return Ok(());
+    }
+    if Path::new(output).exists() && !force {
+        return Err(Error::StringError(format!(
+            "Not overwriting '{}' (use --force to replace it)",
+            output
+        )));
+    }
+    fs::write(output, content)?;
+    println!("Wrote {} sites to {}", websites.len(), output);
+    Ok(())
+}
+
+/// The formats other webring tools keep their member lists in, which `import_website_list()` can read
+pub const IMPORT_FORMATS: &[&str] = &["onionring", "xxiivv", "opml"];
+
+/// Sites read from another tool's member list, plus a note for every entry which couldn't be used
+#[derive(Debug, Default)]
+pub struct ImportedList {
+    pub websites: Vec<Website>,
+    pub skipped: Vec<String>,
+}
+
+impl ImportedList {
+    /// Adds a site, filling in its slug & name from the URL's host if they're missing.
+    /// Sites without a usable web URL, or with a URL already in the list, are skipped.
+    fn push(&mut self, mut website: Website, entry: &str) {
+        let host = match url::Url::parse(website.url.trim()) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {
+                url.host_str().unwrap_or("").trim_start_matches("www.").to_string()
+            }
+            _ => {
+                self.skipped.push(format!("{}: no http(s) URL", entry));
+                return;
+            }
+        };
+        website.url = website.url.trim().to_string();
+        if self.websites.iter().any(|existing| existing.url == website.url) {
+            self.skipped.push(format!("{}: duplicate of {}", entry, website.url));
+            return;
+        }
+
+        if website.slug.is_empty() {
+            website.slug = host.to_lowercase().replace(|c: char| !c.is_alphanumeric(), "-");
+        }
+        // Keep slugs unique, since verify_websites() would refuse the list otherwise
+        let base_slug = website.slug.clone();
+        let mut n = 2;
+        while self.websites.iter().any(|existing| existing.slug == website.slug) {
+            website.slug = format!("{}-{}", base_slug, n);
+            n += 1;
+        }
+        if website.name.is_none() {
+            website.name = Some(host);
+        }
+        self.websites.push(website);
+    }
+}
+
+/// Reads the JS literals (strings, numbers, arrays & objects, with bare or quoted keys) in the files other webring tools use.
+/// Comments and trailing commas are allowed, but anything computed isn't.
+struct JsLiteralReader {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl JsLiteralReader {
+    fn skip_whitespace_and_comments(&mut self) {
+        loop {
+            while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+                self.pos += 1;
+            }
+            match (self.chars.get(self.pos), self.chars.get(self.pos + 1)) {
+                (Some('/'), Some('/')) => {
+                    while self.chars.get(self.pos).is_some_and(|c| *c != '\n') {
+                        self.pos += 1;
+                    }
+                }
+                (Some('/'), Some('*')) => {
+                    self.pos += 2;
+                    while self.pos < self.chars.len()
+                        && !(self.chars[self.pos] == '*' && self.chars.get(self.pos + 1) == Some(&'/'))
+                    {
+                        self.pos += 1;
+                    }
+                    self.pos += 2;
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn error(&self, message: &str) -> Error {
+        let offset: usize = self.chars[..self.pos.min(self.chars.len())].iter().map(|c| c.len_utf8()).sum();
+        let text: String = self.chars.iter().collect();
+        let (line, column) = line_column(&text, offset);
+        Error::StringError(format!("{} at line {}, column {}", message, line, column))
+    }
+
+    fn value(&mut self) -> Result<serde_json::Value, Error> {
+        self.skip_whitespace_and_comments();
+        match self.chars.get(self.pos) {
+            Some('[') => self.array(),
+            Some('{') => self.object(),
+            Some(quote @ ('\'' | '"' | '`')) => {
+                let quote = *quote;
+                Ok(serde_json::Value::String(self.string(quote)?))
+            }
+            Some(_) => self.word(),
+            None => Err(self.error("Unexpected end of file")),
+        }
+    }
+
+    fn string(&mut self, quote: char) -> Result<String, Error> {
+        self.pos += 1;
+        let mut text = String::new();
+        while let Some(&c) = self.chars.get(self.pos) {
+            self.pos += 1;
+            match c {
+                c if c == quote => return Ok(text),
+                '\\' => {
+                    let escaped = self.chars.get(self.pos).copied().unwrap_or('\\');
+                    self.pos += 1;
+                    text.push(match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        other => other,
+                    });
+                }
+                c => text.push(c),
+            }
+        }
+        Err(self.error("Unterminated string"))
+    }
+
+    /// Numbers, true/false/null, and bare object keys
+    fn word(&mut self) -> Result<serde_json::Value, Error> {
+        let start = self.pos;
+        while self
+            .chars
+            .get(self.pos)
+            .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '$' | '.' | '-' | '+'))
+        {
+            self.pos += 1;
+        }
+        let word: String = self.chars[start..self.pos].iter().collect();
+        match word.as_str() {
+            "true" => Ok(serde_json::Value::Bool(true)),
+            "false" => Ok(serde_json::Value::Bool(false)),
+            "null" | "undefined" => Ok(serde_json::Value::Null),
+            _ => match serde_json::from_str::<serde_json::Number>(&word) {
+                Ok(number) => Ok(serde_json::Value::Number(number)),
+                Err(_) if !word.is_empty() => Ok(serde_json::Value::String(word)),
+                Err(_) => {
+                    self.pos = start;
+                    Err(self.error("Unsupported value"))
+                }
+            },
+        }
+    }
+
+    /// Steps over the separator after an item, returning true at the closing bracket
+    fn end_of_item(&mut self, close: char) -> Result<bool, Error> {
+        self.skip_whitespace_and_comments();
+        match self.chars.get(self.pos) {
+            Some(',') => {
+                self.pos += 1;
+                self.skip_whitespace_and_comments();
+                Ok(self.chars.get(self.pos) == Some(&close))
+            }
+            Some(c) if *c == close => Ok(true),
+            _ => Err(self.error(&format!("Expected ',' or '{}'", close))),
+        }
+    }
+
+    fn array(&mut self) -> Result<serde_json::Value, Error> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        self.skip_whitespace_and_comments();
+        while self.chars.get(self.pos) != Some(&']') {
+            items.push(self.value()?);
+            if self.end_of_item(']')? {
+                break;
+            }
+        }
+        self.pos += 1;
+        Ok(serde_json::Value::Array(items))
+    }
+
+    fn object(&mut self) -> Result<serde_json::Value, Error> {
+        self.pos += 1;
+        let mut fields = serde_json::Map::new();
+        self.skip_whitespace_and_comments();
+        while self.chars.get(self.pos) != Some(&'}') {
+            let key = match self.value()? {
+                serde_json::Value::String(key) => key,
+                _ => return Err(self.error("Expected a key")),
+            };
+            self.skip_whitespace_and_comments();
+            if self.chars.get(self.pos) != Some(&':') {
+                return Err(self.error("Expected ':'"));
+            }
+            self.pos += 1;
+            fields.insert(key, self.value()?);
+            if self.end_of_item('}')? {
+                break;
+            }
+        }
+        self.pos += 1;
+        Ok(serde_json::Value::Object(fields))
+    }
+}
+
+/// Finds `<name> = [...]` in a script, and reads the array
+fn read_js_array(script: &str, name: &str) -> Result<Vec<serde_json::Value>, Error> {
+    let pattern = regex::Regex::new(&format!(r"\b{}\s*=\s*\[", regex::escape(name)))
+        .map_err(|e| Error::StringError(e.to_string()))?;
+    let found = pattern
+        .find(script)
+        .ok_or_else(|| Error::StringError(format!("No '{} = [...]' array found", name)))?;
+
+    let mut reader = JsLiteralReader {
+        chars: script.chars().collect(),
+        pos: script[..found.end() - 1].chars().count(),
+    };
+    match reader.value()? {
+        serde_json::Value::Array(items) => Ok(items),
+        _ => Err(Error::StringError(format!("'{}' isn't an array", name))),
+    }
+}
+
+/// Maps a site object from a JS member list. Fields without a `Website` counterpart are kept in `misc`.
+fn website_from_js_object(fields: serde_json::Map<String, serde_json::Value>) -> Website {
+    let mut website = Website::default();
+    let mut misc = serde_json::Map::new();
+    for (key, value) in fields {
+        let text = value.as_str().map(str::to_string);
+        match (key.to_lowercase().as_str(), text) {
+            ("url" | "link" | "href", Some(text)) => website.url = text,
+            ("id" | "slug", Some(text)) => website.slug = text,
+            ("title" | "name", Some(text)) => website.name = Some(text),
+            ("author" | "owner", Some(text)) => website.owner = Some(text),
+            ("description" | "about", Some(text)) => website.about = Some(text),
+            ("rss" | "feed", Some(text)) => website.rss = Some(text),
+            ("atom", Some(text)) => website.atom = Some(text),
+            _ if !value.is_null() => {
+                misc.insert(key, value);
+            }
+            _ => {}
+        }
+    }
+    if !misc.is_empty() {
+        website.misc = Some(serde_json::Value::Object(misc));
+    }
+    website
+}
+
+/// Reads the `sites` array of a JS member list, whose entries are either URLs or site objects.
+/// This covers both onionring.js' `variables.js`, which lists member URLs,
+/// and XXIIVV-style `sites.js` files, whose objects hold `id`, `url`, `title`, `author`, `rss`, etc.
+pub fn import_js_sites(script: &str) -> Result<ImportedList, Error> {
+    let mut imported = ImportedList::default();
+    for (index, entry) in read_js_array(script, "sites")?.into_iter().enumerate() {
+        let label = format!("Entry {}", index + 1);
+        match entry {
+            serde_json::Value::String(url) => imported.push(Website { url: url.clone(), ..Default::default() }, &format!("{} ({})", label, url)),
+            serde_json::Value::Object(fields) => {
+                let website = website_from_js_object(fields);
+                let label = match &website.name {
+                    Some(name) => format!("{} ({})", label, name),
+                    None => label,
+                };
+                imported.push(website, &label);
+            }
+            other => imported.skipped.push(format!("{}: not a URL or site object: {}", label, other)),
+        }
+    }
+    Ok(imported)
+}
+
+/// Reads an OPML subscription list. Each outline with a website URL becomes a site; folders are walked through.
+pub fn import_opml(xml: &str) -> Result<ImportedList, Error> {
+    let document = opml::OPML::from_str(xml)
+        .map_err(|e| Error::StringError(format!("Failed to parse OPML: {}", e)))?;
+
+    fn walk(outlines: &[opml::Outline], imported: &mut ImportedList) {
+        for outline in outlines {
+            let label = outline.title.clone().unwrap_or_else(|| outline.text.clone());
+            let page_url = outline.html_url.clone().or_else(|| {
+                outline
+                    .url
+                    .clone()
+                    .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+            });
+            match page_url {
+                Some(url) => {
+                    let mut website = Website { url, ..Default::default() };
+                    website.name = Some(label.clone()).filter(|name| !name.trim().is_empty());
+                    website.about = outline.description.clone();
+                    match outline.r#type.as_deref() {
+                        Some("atom") => website.atom = outline.xml_url.clone(),
+                        _ => website.rss = outline.xml_url.clone(),
+                    }
+                    imported.push(website, &label);
+                }
+                None if outline.xml_url.is_some() => {
+                    imported.skipped.push(format!("{}: has a feed, but no website URL", label))
+                }
+                // Folders just hold other outlines
+                None if !outline.outlines.is_empty() => {}
+                None => imported.skipped.push(format!("{}: no URL", label)),
+            }
+            walk(&outline.outlines, imported);
+        }
+    }
+
+    let mut imported = ImportedList::default();
+    walk(&document.body.outlines, &mut imported);
+    Ok(imported)
+}
+
+/// Reads another tool's member list ("onionring", "xxiivv" or "opml") from a file or URL
+pub async fn import_website_list(kind: &str, source: &str) -> Result<ImportedList, Error> {
+    let data = acquire_file_data(source).await?;
+    match kind {
+        "onionring" | "xxiivv" => import_js_sites(&data),
+        "opml" => import_opml(&data),
+        other => Err(Error::StringError(format!(
+            "Unsupported import format '{}' (available: {})",
+            other,
+            IMPORT_FORMATS.join(", ")
+        ))),
+    }
+    .map_err(|e| Error::StringError(format!("Failed to import '{}': {}", source, e)))
+}
+
 /// This will either read or download the file, depending on whether a URL or local URI is provided.
 pub async fn acquire_file_data(path_or_url: &str) -> Result<String, Error> {
     // Check if the path_or_url is likely a URL by looking for a scheme
@@ -115,6 +864,239 @@ mod tests {
         assert!(result.is_err(), "Expected error (empty filepath string)");
     }
 
+    fn sample_websites() -> Vec<Website> {
+        vec![
+            Website {
+                slug: "one".into(),
+                name: Some("One, with a comma".into()),
+                url: "https://one.tld".into(),
+                gemini_url: Some("gemini://one.tld".into()),
+                status: Some(MemberStatus::Paused),
+                joined: NaiveDate::from_ymd_opt(2023, 4, 1),
+                active_until: NaiveDate::from_ymd_opt(2030, 1, 31),
+                misc: Some(serde_json::json!({ "tags": ["art", "zines"], "since": 2020 })),
+                private: Some(serde_json::json!({ "email": "one@one.tld", "notes": "Asked for a break" })),
+                ..Default::default()
+            },
+            Website {
+                slug: "other-two".into(),
+                url: "https://two.tld".into(),
+                owner: Some("Two".into()),
+                provenance: Some(crate::website::Provenance {
+                    ring_name: "Other".into(),
+                    ring_hub: "https://other.tld/".into(),
+                    slug: "two".into(),
+                    is_ring: false,
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_website_list_round_trip() {
+        let websites = sample_websites();
+        for format in LIST_FORMATS {
+            let content = serialize_website_list(&websites, format).unwrap();
+            let parsed = parse_website_file(&format!("websites.{}", format), &content).unwrap();
+            assert_eq!(parsed[0], websites[0], "Round trip through {}", format);
+            assert!(content.contains("Other"), "provenance is written to {}", format);
+            assert!(parsed[1].provenance.is_none(), "provenance isn't read back from {}", format);
+            assert_eq!(parsed[0].misc, websites[0].misc, "misc survives {}", format);
+            assert_eq!(parsed[0].private, websites[0].private, "private survives {}", format);
+        }
+        assert!(serialize_website_list(&websites, "xml").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parse_toml_shapes() {
+        let tables = "[[websites]]\nslug = \"a\"\nurl = \"https://a.tld\"\n";
+        let bare = "[{ slug = \"a\", url = \"https://a.tld\" }]";
+        assert_eq!(
+            parse_toml_websites(tables, "TOML literal").unwrap(),
+            parse_toml_websites(bare, "TOML literal").unwrap()
+        );
+        assert!(parse_toml_websites("[websites]\nslug = 1", "TOML literal").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parse_yaml_shapes() {
+        let bare = "- slug: a\n  url: https://a.tld\n  misc:\n    tags: [art]\n";
+        let keyed = "websites:\n  - slug: a\n    url: https://a.tld\n    misc:\n      tags: [art]\n";
+        let from_bare = parse_website_data(bare, "yaml", "YAML literal").unwrap();
+        assert_eq!(from_bare, parse_website_data(keyed, "yml", "YAML literal").unwrap());
+        assert_eq!(from_bare[0].misc, Some(serde_json::json!({ "tags": ["art"] })));
+    }
+
+    #[tokio::test]
+    async fn test_parse_lifecycle_fields() {
+        let expected = parse_website_data(
+            r#"[{"slug": "a", "url": "https://a.tld", "status": "paused", "joined": "2024-05-01"}]"#,
+            "json",
+            "JSON literal",
+        )
+        .unwrap();
+        assert_eq!(expected[0].status, Some(MemberStatus::Paused));
+        assert_eq!(expected[0].joined, NaiveDate::from_ymd_opt(2024, 5, 1));
+
+        // TOML & YAML dates don't need quotes
+        let toml = "[[websites]]\nslug = \"a\"\nurl = \"https://a.tld\"\nstatus = \"paused\"\njoined = 2024-05-01\n";
+        assert_eq!(parse_website_data(toml, "toml", "TOML literal").unwrap(), expected);
+        let yaml = "- slug: a\n  url: https://a.tld\n  status: paused\n  joined: 2024-05-01\n";
+        assert_eq!(parse_website_data(yaml, "yaml", "YAML literal").unwrap(), expected);
+        let member = parse_member_file("url = \"https://a.tld\"\nstatus = \"paused\"\njoined = 2024-05-01\n", "toml", "a", "member")
+            .unwrap()
+            .unwrap();
+        assert_eq!(vec![member.1], expected);
+
+        let error = parse_website_data(r#"[{"slug": "a", "url": "https://a.tld", "joined": "May 1st"}]"#, "json", "JSON literal")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("invalid date 'May 1st'"), "{}", error);
+        assert!(parse_website_data(r#"[{"slug": "a", "url": "https://a.tld", "status": "gone"}]"#, "json", "JSON literal").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parse_errors_have_locations() {
+        let cases = [
+            ("json", "[\n  {\"slug\": \"a\"}\n]", "line 2"),
+            ("toml", "[[websites]]\nslug = \"a\"\nurl = \n", "line 3"),
+            ("toml", "[{ slug = \"a\" }]", "line 1, column 2"),
+            ("yaml", "- slug: a\n  url: [\n", "line 3"),
+        ];
+        for (format, data, location) in cases {
+            let error = parse_website_data(data, format, "list").unwrap_err().to_string();
+            assert!(error.contains(location), "{} error should point at {}: {}", format, location, error);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_onionring() {
+        let script = r#"
+// onionring.js is made up of four files - onionring-widget.js, onionring-index.js, onionring-variables.js (this one!), and onionring.css
+var sites = [
+'https://example.com/',
+"https://www.another.net/page.html", // a comment
+/* 'https://commented-out.tld', */
+'mailto:someone@example.com',
+'https://example.com/',
+];
+
+var ringName = 'Example Ring';
+"#;
+        let imported = import_js_sites(script).unwrap();
+        let urls: Vec<_> = imported.websites.iter().map(|website| website.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://example.com/", "https://www.another.net/page.html"]);
+        assert_eq!(imported.websites[1].name.as_deref(), Some("another.net"));
+        assert_eq!(imported.websites[0].slug, "example-com");
+        assert_eq!(imported.websites[1].slug, "another-net");
+        assert_eq!(imported.skipped.len(), 2, "mailto: and the duplicate: {:?}", imported.skipped);
+    }
+
+    #[tokio::test]
+    async fn test_import_xxiivv() {
+        let script = r#"
+'use strict'
+
+const sites = [
+  {
+    id: 'xxiivv',
+    title: 'XXIIVV',
+    author: 'Devine',
+    url: 'https://wiki.xxiivv.com',
+    rss: 'https://wiki.xxiivv.com/links/rss.xml',
+    langs: ['en'],
+  },
+  { id: 'xxiivv', url: 'https://other.tld' },
+  { title: 'No URL' },
+  42,
+]
+"#;
+        let imported = import_js_sites(script).unwrap();
+        assert_eq!(imported.websites.len(), 2);
+        let first = &imported.websites[0];
+        assert_eq!(first.slug, "xxiivv");
+        assert_eq!(first.name.as_deref(), Some("XXIIVV"));
+        assert_eq!(first.owner.as_deref(), Some("Devine"));
+        assert_eq!(first.rss.as_deref(), Some("https://wiki.xxiivv.com/links/rss.xml"));
+        assert_eq!(first.misc, Some(serde_json::json!({ "langs": ["en"] })));
+        assert_eq!(imported.websites[1].slug, "xxiivv-2", "Slugs are kept unique");
+        assert_eq!(imported.skipped.len(), 2, "{:?}", imported.skipped);
+
+        assert!(import_js_sites("const sites = [{ url: location.href }").is_err());
+        assert!(import_js_sites("const members = []").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_opml() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<opml version="2.0">
+  <head><title>Subscriptions</title></head>
+  <body>
+    <outline text="Blogs">
+      <outline text="One" type="rss" xmlUrl="https://one.tld/feed.xml" htmlUrl="https://one.tld/" description="The first"/>
+      <outline text="Two" type="atom" xmlUrl="https://two.tld/atom.xml" htmlUrl="https://two.tld/"/>
+    </outline>
+    <outline text="Feed only" type="rss" xmlUrl="https://three.tld/feed.xml"/>
+    <outline text="A link" type="link" url="https://four.tld/"/>
+  </body>
+</opml>"#;
+        let imported = import_opml(xml).unwrap();
+        let names: Vec<_> = imported.websites.iter().map(|website| website.name.as_deref().unwrap()).collect();
+        assert_eq!(names, vec!["One", "Two", "A link"]);
+        assert_eq!(imported.websites[0].rss.as_deref(), Some("https://one.tld/feed.xml"));
+        assert_eq!(imported.websites[0].about.as_deref(), Some("The first"));
+        assert_eq!(imported.websites[1].atom.as_deref(), Some("https://two.tld/atom.xml"));
+        assert_eq!(imported.skipped, vec!["Feed only: has a feed, but no website URL"]);
+    }
+
+    #[tokio::test]
+    async fn test_parse_website_directory() {
+        let directory = std::env::temp_dir().join(format!("ringfairy-members-dir-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        let write = |name: &str, content: &str| fs::write(directory.join(name), content).unwrap();
+
+        write("alpha.toml", "name = \"Alpha\"\nurl = \"https://alpha.tld\"\n");
+        write("bravo.json", r#"{"url": "https://bravo.tld", "order": 1, "misc": {"note": "first"}}"#);
+        write("charlie.md", "---\nname: Charlie\nurl: https://charlie.tld\n---\n\nCharlie's *blog*.\n");
+        write("delta.md", "+++\nslug = \"d\"\nurl = \"https://delta.tld\"\nabout = \"Set\"\n+++\nBody\n");
+        write("_template.toml", "url = \"not a member\"\n");
+        write("notes.txt", "not a member either");
+
+        let dir = directory.to_str().unwrap();
+        let members = parse_website_directory(dir).unwrap();
+        let slugs: Vec<_> = members.iter().map(|website| website.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["bravo", "alpha", "charlie", "d"], "`order` first, then file names");
+        assert_eq!(members[0].misc, Some(serde_json::json!({ "note": "first" })));
+        assert_eq!(members[2].about.as_deref(), Some("Charlie's *blog*."));
+        assert_eq!(members[3].about.as_deref(), Some("Set"));
+
+        // The order file wins; anything missing from it goes at the end
+        write(MEMBER_ORDER_FILE, "# ring order\ncharlie\nd\nalpha\nmissing\n");
+        let members = parse_website_directory(dir).unwrap();
+        let slugs: Vec<_> = members.iter().map(|website| website.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["charlie", "d", "alpha", "bravo"]);
+
+        write("echo.yaml", "url: https://echo.tld\nname: [\n");
+        let error = parse_website_directory(dir).unwrap_err().to_string();
+        assert!(error.contains("echo.yaml") && error.contains("line 3"), "{}", error);
+        fs::remove_file(directory.join("echo.yaml")).unwrap();
+
+        write("foxtrot.md", "---\nname: Foxtrot\nurl: [\n---\n");
+        let error = parse_website_directory(dir).unwrap_err().to_string();
+        assert!(error.contains("foxtrot.md") && error.contains("line 4"), "{}", error);
+        fs::remove_file(directory.join("foxtrot.md")).unwrap();
+
+        for order in ["\"3\"", "1.5"] {
+            write("golf.toml", &format!("url = \"https://golf.tld\"\norder = {}\n", order));
+            let error = parse_website_directory(dir).unwrap_err().to_string();
+            assert!(error.contains("golf.toml") && error.contains("'order'"), "{}", error);
+        }
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
     // get_extension_from_path()
     #[tokio::test]
     async fn test_get_extension_from_valid_path() {
@@ -5,23 +5,40 @@ use std::fs::{self};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
+pub mod gemini;
+pub mod gopher;
 pub mod html;
+pub mod json;
+pub mod onionring;
+pub mod server;
 #[cfg(test)]
 mod tests;
+pub mod theme;
 pub mod webring;
 
 use crate::cli::AppSettings;
 use crate::error::Error;
 use crate::file::copy_asset_files;
+use crate::gen::theme::write_theme_assets;
 use crate::gen::webring::WebringSiteList;
 
 ///Entry point (for now)
 pub async fn make_ringfairy_go_now(settings: &AppSettings) -> Result<(), Error> {
+    // Write the built-in theme's files first, so the assets folder can override them
+    write_theme_assets(&settings.theme, &settings.path_output)?;
+
     // Do webring
     webring::generate_webring_files(settings).await?;
 
     // Copy static files (from ./assets by default) into output folder
-    copy_asset_files(&settings.path_assets, &settings.path_output).await?;
+    if Path::new(&settings.path_assets).is_dir() {
+        copy_asset_files(&settings.path_assets, &settings.path_output).await?;
+    } else {
+        log::info!(
+            "Assets folder '{}' not found; using the built-in theme only.",
+            settings.path_assets
+        );
+    }
 
     Ok(())
 }
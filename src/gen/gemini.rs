@@ -0,0 +1,124 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::AppSettings;
+use crate::error::Error;
+use crate::gen::{server::has_server_redirects, webring::WebringSiteList, Generator};
+use crate::website::Website;
+
+/// Builds a Gemini capsule mirroring the webring, for every member with a `gemini_url`.
+/// Real redirects come from the `gmid` server config; the next/previous pages written here are single links, for servers without one.
+pub struct GeminiGenerator;
+
+impl Generator for GeminiGenerator {
+    async fn new(_template_path: PathBuf, _skip_minify: bool) -> Result<Self, Error> {
+        Ok(Self)
+    }
+
+    async fn write_content(&self, file_path: &Path, content: &str) -> Result<(), Error> {
+        fs::write(file_path, content)?;
+        log::info!("Generated gemtext file {}", file_path.display());
+        Ok(())
+    }
+
+    async fn generate_content(
+        &self,
+        webring: &WebringSiteList,
+        settings: &AppSettings,
+    ) -> Result<(), Error> {
+        let members = gemini_members(webring);
+        if members.is_empty() {
+            log::warn!("No sites have a Gemini URL; skipping the capsule.");
+            return Ok(());
+        }
+
+        let path_output = Path::new(&settings.path_output_gemini);
+        self.ensure_output_directory(&settings.path_output_gemini)
+            .await?;
+        self.write_content(
+            &path_output.join("index.gmi"),
+            &build_gemini_hub(&members, settings),
+        )
+        .await?;
+
+        // The server config can take care of the redirects instead
+        if settings.skip_redirect_pages && has_server_redirects(settings, true) {
+            return Ok(());
+        }
+
+        // Static capsules can't send redirect responses, so each redirect is a page with a single link
+        for (index, (website, _)) in members.iter().enumerate() {
+            let next = &members[(index + 1) % members.len()];
+            let previous = &members[(index + members.len() - 1) % members.len()];
+
+            for (url_text, (target, target_url)) in [
+                (&settings.next_url_text, next),
+                (&settings.prev_url_text, previous),
+            ] {
+                let page_path = path_output.join(&website.slug).join(url_text);
+                fs::create_dir_all(&page_path)?;
+                self.write_content(
+                    &page_path.join("index.gmi"),
+                    &format!(
+                        "# {}\n\n=> {} {}\n",
+                        settings.ring_name,
+                        target_url,
+                        display_name(target)
+                    ),
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Lists the ring's sites which have a Gemini URL, in ring order
+pub fn gemini_members(webring: &WebringSiteList) -> Vec<(&Website, &str)> {
+    webring
+        .sites
+        .iter()
+        .filter_map(|site| {
+            site.website
+                .gemini_url
+                .as_deref()
+                .filter(|url| !url.is_empty())
+                .map(|url| (&site.website, url))
+        })
+        .collect()
+}
+
+/// Builds the gemtext hub page listing all members of the capsule
+pub fn build_gemini_hub(members: &[(&Website, &str)], settings: &AppSettings) -> String {
+    let gemini_base_url = settings.gemini_base_url.trim_end_matches('/');
+    let mut hub = format!("# {}\n\n{}\n\n", settings.ring_name, settings.ring_description);
+
+    hub.push_str("## Members\n\n");
+    for (website, gemini_url) in members {
+        hub.push_str(&format!("=> {} {}\n", gemini_url, display_name(website)));
+        if let Some(about) = website.about.as_deref().filter(|about| !about.is_empty()) {
+            hub.push_str(&format!("{}\n", about));
+        }
+        hub.push('\n');
+    }
+
+    hub.push_str("## Joining\n\n");
+    hub.push_str(&format!(
+        "Members link to {base}/<slug>/{next}/ and {base}/<slug>/{prev}/ from their capsule.\n\n",
+        base = gemini_base_url,
+        next = settings.next_url_text,
+        prev = settings.prev_url_text
+    ));
+    hub.push_str(&format!("=> {} {} on the web\n", settings.base_url.trim(), settings.ring_name));
+    hub.push_str(&format!(
+        "=> {} Maintained by {}\n",
+        settings.ring_owner_site, settings.ring_owner
+    ));
+
+    hub
+}
+
+fn display_name(website: &Website) -> &str {
+    website.name.as_deref().unwrap_or(&website.slug)
+}
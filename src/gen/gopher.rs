@@ -0,0 +1,187 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::AppSettings;
+use crate::error::Error;
+use crate::gen::{webring::WebringSiteList, Generator};
+use crate::website::Website;
+
+/// Builds a gopher hole mirroring the webring, for every member with a `gopher_url`
+pub struct GopherGenerator;
+
+/// A gopher menu item, as described by a `gopher://` URL
+#[derive(Debug, PartialEq, Eq)]
+pub struct GopherItem {
+    pub item_type: char,
+    pub selector: String,
+    pub host: String,
+    pub port: u16,
+}
+
+impl GopherItem {
+    /// Parses a URL like `gopher://example.com:70/1/phlog`, where the first path character is the item type
+    pub fn from_url(url: &str) -> Option<Self> {
+        let parsed = url::Url::parse(url).ok()?;
+        if parsed.scheme() != "gopher" {
+            return None;
+        }
+        let path = parsed.path().trim_start_matches('/');
+        let mut chars = path.chars();
+        let item_type = chars.next().unwrap_or('1');
+        Some(GopherItem {
+            item_type,
+            selector: chars.as_str().to_string(),
+            host: parsed.host_str()?.to_string(),
+            port: parsed.port().unwrap_or(70),
+        })
+    }
+
+    /// Formats this item as a gophermap line with the given display text
+    pub fn menu_line(&self, display: &str) -> String {
+        format!(
+            "{}{}\t{}\t{}\t{}\n",
+            self.item_type,
+            menu_text(display),
+            self.selector,
+            self.host,
+            self.port
+        )
+    }
+}
+
+impl Generator for GopherGenerator {
+    async fn new(_template_path: PathBuf, _skip_minify: bool) -> Result<Self, Error> {
+        Ok(Self)
+    }
+
+    async fn write_content(&self, file_path: &Path, content: &str) -> Result<(), Error> {
+        fs::write(file_path, content)?;
+        log::info!("Generated gophermap {}", file_path.display());
+        Ok(())
+    }
+
+    async fn generate_content(
+        &self,
+        webring: &WebringSiteList,
+        settings: &AppSettings,
+    ) -> Result<(), Error> {
+        let base = GopherItem::from_url(&settings.gopher_base_url).ok_or_else(|| {
+            Error::StringError(format!(
+                "Unrecognized Gopher base URL: {}",
+                settings.gopher_base_url
+            ))
+        })?;
+
+        let members = gopher_members(webring);
+        if members.is_empty() {
+            log::warn!("No sites have a Gopher URL; skipping the gopher hole.");
+            return Ok(());
+        }
+
+        let path_output = Path::new(&settings.path_output_gopher);
+        self.ensure_output_directory(&settings.path_output_gopher)
+            .await?;
+        self.write_content(
+            &path_output.join("gophermap"),
+            &build_gopher_hub(&members, &base, settings),
+        )
+        .await?;
+
+        for (index, (website, _)) in members.iter().enumerate() {
+            let next = &members[(index + 1) % members.len()];
+            let previous = &members[(index + members.len() - 1) % members.len()];
+
+            for (url_text, (target, target_item)) in [
+                (&settings.next_url_text, next),
+                (&settings.prev_url_text, previous),
+            ] {
+                let menu_path = path_output.join(&website.slug).join(url_text);
+                fs::create_dir_all(&menu_path)?;
+                let mut menu = info_line(&format!("{} ({})", settings.ring_name, url_text));
+                menu.push_str(&target_item.menu_line(display_name(target)));
+                self.write_content(&menu_path.join("gophermap"), &menu)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Lists the ring's sites which have a valid Gopher URL, in ring order
+pub fn gopher_members(webring: &WebringSiteList) -> Vec<(&Website, GopherItem)> {
+    webring
+        .sites
+        .iter()
+        .filter_map(|site| {
+            let url = site.website.gopher_url.as_deref()?;
+            match GopherItem::from_url(url) {
+                Some(item) => Some((&site.website, item)),
+                None => {
+                    log::warn!("Skipping unrecognized Gopher URL: {}", url);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Builds the hub gophermap listing all members of the gopher hole
+pub fn build_gopher_hub(
+    members: &[(&Website, GopherItem)],
+    base: &GopherItem,
+    settings: &AppSettings,
+) -> String {
+    let mut hub = info_line(&settings.ring_name);
+    hub.push_str(&info_line(&settings.ring_description));
+    hub.push_str(&info_line(""));
+
+    for (website, item) in members {
+        hub.push_str(&item.menu_line(display_name(website)));
+        if let Some(about) = website.about.as_deref().filter(|about| !about.is_empty()) {
+            hub.push_str(&info_line(about));
+        }
+        // Each member's own next/previous menus within this hole
+        for url_text in [&settings.next_url_text, &settings.prev_url_text] {
+            let menu = GopherItem {
+                item_type: '1',
+                selector: format!(
+                    "{}/{}/{}",
+                    base.selector.trim_end_matches('/'),
+                    website.slug,
+                    url_text
+                ),
+                host: base.host.clone(),
+                port: base.port,
+            };
+            hub.push_str(&menu.menu_line(&format!("  {}", url_text)));
+        }
+        hub.push_str(&info_line(""));
+    }
+
+    let selector = base.selector.trim_end_matches('/');
+    hub.push_str(&info_line(&format!(
+        "Members link to the menus {selector}/<slug>/{} and {selector}/<slug>/{} on {}:{} from their gopher hole.",
+        settings.next_url_text,
+        settings.prev_url_text,
+        base.host,
+        base.port
+    )));
+    hub.push_str(&info_line(&format!("Maintained by {}", settings.ring_owner)));
+
+    hub
+}
+
+/// Formats an informational (non-link) gophermap line
+fn info_line(text: &str) -> String {
+    format!("i{}\tfake\t(NULL)\t0\n", menu_text(text))
+}
+
+/// Replaces tabs & line breaks, which would split a gophermap line, with spaces
+fn menu_text(text: &str) -> String {
+    text.replace(['\t', '\r', '\n'], " ")
+}
+
+fn display_name(website: &Website) -> &str {
+    website.name.as_deref().unwrap_or(&website.slug)
+}
@@ -1,5 +1,5 @@
 use minify_html::{minify, Cfg};
-use opml::{Head, OPML};
+use opml::{Head, Outline, OPML};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tera::{Context, Tera};
@@ -7,8 +7,27 @@ use tera::{Context, Tera};
 use super::*;
 use crate::cli::AppSettings;
 use crate::error::Error;
+use crate::website::{canonicalize_url, ownership_token, Website, VERIFICATION_META_NAME};
+use crate::gen::server::has_server_redirects;
+use crate::gen::theme::fallback_templates;
 use crate::gen::{webring::WebringSite, webring::WebringSiteList, Generator, PrecomputedTags};
 
+/// Maps a member (by canonical URL) to where the universal page should send its visitors
+#[derive(serde::Serialize)]
+struct UniversalTarget {
+    key: String,
+    url: String,
+}
+
+/// Describes one redirect page: where the visitor comes from, and where they're headed
+struct RedirectInfo<'a> {
+    direction: &'a str,
+    source_site: &'a WebringSite,
+    source_position: usize,
+    target_site: &'a WebringSite,
+    target_position: usize,
+}
+
 pub struct HtmlGenerator {
     tera: Tera,
     cfg: Cfg,
@@ -21,8 +40,17 @@ impl Generator for HtmlGenerator {
         cfg.minify_css = true;
         cfg.minify_js = true;
 
-        let template_path_str = template_path.join("**/*").to_string_lossy().to_string();
-        let tera = Tera::new(&template_path_str)?;
+        let tera = if template_path.is_dir() {
+            let template_path_str = template_path.join("**/*").to_string_lossy().to_string();
+            Tera::new(&template_path_str)?
+        } else {
+            log::warn!(
+                "Templates folder '{}' not found; using the built-in templates.",
+                template_path.display()
+            );
+            Tera::default()
+        };
+
         Ok(Self {
             tera,
             cfg,
@@ -52,10 +80,10 @@ impl Generator for HtmlGenerator {
         self.ensure_output_directory(&settings.path_output).await?;
         let precomputed = <HtmlGenerator as Generator>::precompute_tags(webring, settings).await;
         let context = self
-            .generate_context(&webring, &precomputed, settings)
+            .generate_context(webring, &precomputed, settings)
             .await?;
 
-        self.generate_html(&webring, settings, &context).await?;
+        self.generate_html(webring, settings, &context).await?;
         self.generate_opml(&webring.sites, settings).await?;
 
         Ok(())
@@ -63,6 +91,16 @@ impl Generator for HtmlGenerator {
 }
 
 impl HtmlGenerator {
+    /// Falls back to the built-in templates for anything the user hasn't provided (see `fallback_templates`)
+    pub fn add_fallback_templates(&mut self, settings: &AppSettings) -> Result<(), Error> {
+        let missing_templates: Vec<(String, &str)> = fallback_templates(settings)
+            .into_iter()
+            .filter(|(name, _)| !self.has_template(name))
+            .collect();
+        self.tera.add_raw_templates(missing_templates)?;
+        Ok(())
+    }
+
     async fn generate_html(
         &self,
         webring: &WebringSiteList,
@@ -70,13 +108,31 @@ impl HtmlGenerator {
         context: &Context,
     ) -> Result<(), Error> {
         // Generate site-specific pages
-        for site in &webring.sites {
-            self.generate_site(&site, webring, context, &settings.path_output, settings)
+        for (index, site) in webring.sites.iter().enumerate() {
+            self.generate_site(index, site, webring, context, &settings.path_output, settings)
                 .await?;
         }
 
+        // Top-level next/previous pages which look up the referrer
+        if settings.universal_links {
+            if self.has_template(&settings.filename_template_universal) {
+                self.generate_universal_pages(webring, context, settings)
+                    .await?;
+            } else {
+                log::warn!(
+                    "Universal links are enabled, but template '{}' wasn't found; skipping them.",
+                    settings.filename_template_universal
+                );
+            }
+        }
+
         // Process all other custom templates
-        self.generate_custom_templates(settings, webring).await?;
+        let pages = self.generate_custom_templates(settings, webring).await?;
+
+        // Help search engines find the hub pages, and skip the redirects
+        if !settings.skip_seo {
+            self.generate_seo_files(&pages, settings).await?;
+        }
         Ok(())
     }
 
@@ -108,6 +164,18 @@ impl HtmlGenerator {
                     opml.add_feed(owner, rss_url);
                 }
             }
+            if let Some(gemini_url) = website.website.gemini_url.as_ref().filter(|url| !url.is_empty()) {
+                opml.body.outlines.push(Outline {
+                    text: website
+                        .website
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| website.website.slug.clone()),
+                    r#type: Some("link".into()),
+                    url: Some(gemini_url.to_owned()),
+                    ..Outline::default()
+                });
+            }
         }
 
         let mut file =
@@ -122,6 +190,7 @@ impl HtmlGenerator {
 
     async fn generate_site(
         &self,
+        index: usize,
         site: &WebringSite,
         webring: &WebringSiteList,
         context: &Context,
@@ -129,44 +198,202 @@ impl HtmlGenerator {
         settings: &AppSettings,
     ) -> Result<(), Error> {
         let site_path = Path::new(path_output).join(&site.website.slug);
+        fs::create_dir_all(&site_path)?;
+
+        // Server configs can take care of the redirects instead
+        if settings.skip_redirect_pages && has_server_redirects(settings, false) {
+            log::debug!("Skipping redirect pages for {}", site.website.slug);
+        } else {
+            self.generate_redirect_pages(index, site, webring, &site_path, context, settings)
+                .await?;
+        }
+
+        // Per-site join page showing the embed code for this site
+        if self.has_template(&settings.filename_template_join) {
+            self.generate_join_page(site, webring, &site_path, context, settings)
+                .await?;
+        }
+
+        // Per-site landing page, so the bare /slug/ URL doesn't 404
+        if self.has_template(&settings.filename_template_member) {
+            let member_context = site_context(site, webring, context);
+            let content = self
+                .tera
+                .render(&settings.filename_template_member, &member_context)?;
+            self.write_content(&site_path.join("index.html"), &content)
+                .await?;
+        }
+
+        // Per-site random page, which never sends visitors back where they came from
+        if self.has_template(&settings.filename_template_random) {
+            fs::create_dir_all(site_path.join(&settings.random_url_text))?;
+            let mut random_context = site_context(site, webring, context);
+            insert_random_targets(
+                &mut random_context,
+                &random_candidates(webring, Some(index), settings),
+                settings,
+            );
+            let content = mark_as_redirect(
+                self.tera
+                    .render(&settings.filename_template_random, &random_context)?,
+                None,
+                settings,
+            );
+            self.write_content(
+                &site_path.join(format!("{}/index.html", settings.random_url_text)),
+                &content,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn generate_redirect_pages(
+        &self,
+        index: usize,
+        site: &WebringSite,
+        webring: &WebringSiteList,
+        site_path: &Path,
+        context: &Context,
+        settings: &AppSettings,
+    ) -> Result<(), Error> {
         fs::create_dir_all(site_path.join(&settings.next_url_text))?;
         fs::create_dir_all(site_path.join(&settings.prev_url_text))?;
 
-        let previous_site = &webring.sites[site.previous].website.url;
-        let next_site = &webring.sites[site.next].website.url;
-
         self.render_and_write(
-            &site_path,
+            site_path,
             &settings.next_url_text,
-            next_site,
+            RedirectInfo {
+                direction: "next",
+                source_site: site,
+                source_position: index + 1,
+                target_site: &webring.sites[site.next],
+                target_position: site.next + 1,
+            },
             &settings.filename_template_redirect,
             context,
+            settings,
         )
         .await?;
         self.render_and_write(
-            &site_path,
+            site_path,
             &settings.prev_url_text,
-            previous_site,
+            RedirectInfo {
+                direction: "previous",
+                source_site: site,
+                source_position: index + 1,
+                target_site: &webring.sites[site.previous],
+                target_position: site.previous + 1,
+            },
             &settings.filename_template_redirect,
             context,
+            settings,
         )
         .await?;
 
         Ok(())
     }
 
+    async fn generate_join_page(
+        &self,
+        site: &WebringSite,
+        webring: &WebringSiteList,
+        site_path: &Path,
+        context: &Context,
+        settings: &AppSettings,
+    ) -> Result<(), Error> {
+        let mut join_context = site_context(site, webring, context);
+        join_context.insert("next_url_text", &settings.next_url_text);
+        join_context.insert("prev_url_text", &settings.prev_url_text);
+        join_context.insert("snippets", &build_join_snippets(&site.website, settings));
+
+        let content = self
+            .tera
+            .render(&settings.filename_template_join, &join_context)?;
+        let file_name = Path::new(&settings.filename_template_join)
+            .file_name()
+            .unwrap_or_default();
+        self.write_content(&site_path.join(file_name), &content)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn generate_universal_pages(
+        &self,
+        webring: &WebringSiteList,
+        context: &Context,
+        settings: &AppSettings,
+    ) -> Result<(), Error> {
+        let path_output = Path::new(&settings.path_output);
+
+        for (direction, url_text) in [
+            ("next", &settings.next_url_text),
+            ("previous", &settings.prev_url_text),
+        ] {
+            let targets: Vec<UniversalTarget> = webring
+                .sites
+                .iter()
+                .map(|site| {
+                    let target = if direction == "next" {
+                        site.next
+                    } else {
+                        site.previous
+                    };
+                    UniversalTarget {
+                        key: canonicalize_url(&site.website.url),
+                        url: webring.sites[target].website.url.clone(),
+                    }
+                })
+                .collect();
+
+            let mut universal_context = context.clone();
+            universal_context.insert("direction", direction);
+            universal_context.insert("universal_targets", &targets);
+
+            let content = mark_as_redirect(
+                self.tera
+                    .render(&settings.filename_template_universal, &universal_context)?,
+                None,
+                settings,
+            );
+            fs::create_dir_all(path_output.join(url_text))?;
+            self.write_content(&path_output.join(format!("{}/index.html", url_text)), &content)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    fn has_template(&self, template_name: &str) -> bool {
+        self.tera
+            .get_template_names()
+            .any(|name| name == template_name)
+    }
+
     async fn render_and_write(
         &self,
         site_path: &Path,
         url_text: &str,
-        site_url: &str,
+        redirect: RedirectInfo<'_>,
         template_name: &str,
         context: &Context,
+        settings: &AppSettings,
     ) -> Result<(), Error> {
         let mut url_context = context.clone();
-        url_context.insert("url", site_url);
-
-        let content = self.tera.render(template_name, &url_context)?;
+        url_context.insert("url", &redirect.target_site.website.url);
+        url_context.insert("direction", redirect.direction);
+        url_context.insert("source_site", redirect.source_site);
+        url_context.insert("source_position", &redirect.source_position);
+        url_context.insert("target_site", redirect.target_site);
+        url_context.insert("target_position", &redirect.target_position);
+
+        let content = mark_as_redirect(
+            self.tera.render(template_name, &url_context)?,
+            Some(&redirect.target_site.website.url),
+            settings,
+        );
         self.write_content(
             &site_path.join(format!("{}/index.html", url_text)),
             &content,
@@ -180,21 +407,58 @@ impl HtmlGenerator {
         &self,
         settings: &AppSettings,
         webring: &WebringSiteList,
-    ) -> Result<(), Error> {
+    ) -> Result<Vec<String>, Error> {
         let path_output = &settings.path_output;
+        let mut pages = Vec::new();
 
         let precomputed = <HtmlGenerator as Generator>::precompute_tags(webring, settings).await;
 
+        // The special templates are never pages in their own right, whether under their configured names or the built-in ones
+        let defaults = AppSettings::default();
+        let special_templates = [
+            &settings.filename_template_redirect,
+            &settings.filename_template_join,
+            &settings.filename_template_member,
+            &settings.filename_template_universal,
+            &defaults.filename_template_redirect,
+            &defaults.filename_template_join,
+            &defaults.filename_template_member,
+            &defaults.filename_template_universal,
+            &defaults.filename_template_random,
+        ];
         for template_name in self.tera.get_template_names().filter(|name| {
-            *name != settings.filename_template_redirect
+            *name == settings.filename_template_random
+                || !special_templates.iter().any(|special| special.as_str() == *name)
         }) {
             let context = self
                 .generate_context(webring, &precomputed, settings)
                 .await?;
-            let content = self.tera.render(template_name, &context)?;
+            let mut content = self.tera.render(template_name, &context)?;
+            if template_name == settings.filename_template_random {
+                content = mark_as_redirect(content, None, settings);
+            } else {
+                pages.push(template_name.to_string());
+            }
             let file_path = Path::new(path_output).join(template_name);
             self.write_content(&file_path, &content).await?;
         }
+        Ok(pages)
+    }
+
+    async fn generate_seo_files(&self, pages: &[String], settings: &AppSettings) -> Result<(), Error> {
+        let path_output = Path::new(&settings.path_output);
+
+        fs::write(path_output.join("robots.txt"), build_robots_txt(settings))?;
+        log::info!("Generated robots.txt");
+
+        // Search engines need absolute URLs, which aren't possible without a base URL
+        if settings.base_url.trim().is_empty() {
+            log::warn!("No base URL set; skipping sitemap.xml.");
+        } else {
+            fs::write(path_output.join("sitemap.xml"), build_sitemap_xml(pages, settings))?;
+            log::info!("Generated sitemap.xml");
+        }
+
         Ok(())
     }
 
@@ -223,13 +487,219 @@ impl HtmlGenerator {
         context.insert("featured_site_url", &precomputed.featured_site_url);
         context.insert("current_time", &precomputed.current_time);
         context.insert("opml", &precomputed.opml_link);
+        context.insert(
+            "join_page",
+            &Path::new(&settings.filename_template_join)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string()),
+        );
         context.insert("sites", &webring.sites); // Insert the whole list
         context.insert("failed_sites", &webring.failed_sites);
+        context.insert("paused_sites", &webring.paused_sites);
+        context.insert("upcoming_sites", &webring.upcoming_sites);
+        insert_random_targets(
+            &mut context,
+            &random_candidates(webring, None, settings),
+            settings,
+        );
 
         Ok(context)
     }
 }
 
+/// Adds noindex (and optionally canonical) tags to a rendered redirect page, unless SEO is skipped
+fn mark_as_redirect(content: String, canonical_url: Option<&str>, settings: &AppSettings) -> String {
+    if settings.skip_seo {
+        return content;
+    }
+    let mut tags = String::from("<meta name=\"robots\" content=\"noindex\">");
+    if let Some(url) = canonical_url {
+        tags.push_str(&format!("<link rel=\"canonical\" href=\"{}\">", url));
+    }
+    inject_head_tags(&content, &tags)
+}
+
+/// Inserts tags at the end of the page's <head>, or at the very start if it doesn't have one
+pub fn inject_head_tags(html: &str, tags: &str) -> String {
+    match html.find("</head>").or_else(|| html.find("</HEAD>")) {
+        Some(index) => format!("{}{}{}", &html[..index], tags, &html[index..]),
+        None => format!("{}{}", tags, html),
+    }
+}
+
+/// Lists the hub/custom HTML pages (by template name) for search engines. Anything else built from a template, e.g. a feed, is left out.
+pub fn build_sitemap_xml(pages: &[String], settings: &AppSettings) -> String {
+    let base_url = settings.base_url.trim().trim_end_matches('/');
+    let lastmod = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+    let mut sitemap = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for page in pages.iter().filter(|page| {
+        let page = page.to_lowercase();
+        page.ends_with(".html") || page.ends_with(".htm")
+    }) {
+        let page = page.replace('\\', "/");
+        let loc = if page == "index.html" {
+            format!("{}/", base_url)
+        } else {
+            format!("{}/{}", base_url, page)
+        };
+        sitemap.push_str(&format!(
+            "  <url><loc>{}</loc><lastmod>{}</lastmod></url>\n",
+            loc.replace('&', "&amp;").replace('<', "&lt;"),
+            lastmod
+        ));
+    }
+    sitemap.push_str("</urlset>\n");
+    sitemap
+}
+
+/// Lets crawlers in everywhere, and points them to the sitemap.
+/// Redirect pages are kept out of search results by their `noindex` tag, which crawlers only see if they may fetch the page.
+pub fn build_robots_txt(settings: &AppSettings) -> String {
+    let mut robots = String::from("User-agent: *\nDisallow:\n");
+    if !settings.base_url.trim().is_empty() {
+        robots.push_str(&format!(
+            "\nSitemap: {}/sitemap.xml\n",
+            settings.base_url.trim().trim_end_matches('/')
+        ));
+    }
+    robots
+}
+
+/// Extends the shared context with a site and its neighbors in the ring
+fn site_context(site: &WebringSite, webring: &WebringSiteList, context: &Context) -> Context {
+    let mut site_context = context.clone();
+    site_context.insert("site", site);
+    site_context.insert("previous_site", &webring.sites[site.previous]);
+    site_context.insert("next_site", &webring.sites[site.next]);
+    site_context
+}
+
+/// Lists the sites which may be picked as a random destination.
+/// With an origin site, the origin itself (and optionally its neighbors) is left out.
+pub fn random_candidates<'a>(
+    webring: &'a WebringSiteList,
+    origin: Option<usize>,
+    settings: &AppSettings,
+) -> Vec<&'a WebringSite> {
+    webring
+        .sites
+        .iter()
+        .enumerate()
+        .filter(|(index, site)| {
+            if settings.random_exclude.contains(&site.website.slug) {
+                return false;
+            }
+            match origin.map(|origin| (origin, &webring.sites[origin])) {
+                Some((origin, origin_site)) => {
+                    *index != origin
+                        && !(settings.random_exclude_neighbors
+                            && (*index == origin_site.next || *index == origin_site.previous))
+                }
+                None => true,
+            }
+        })
+        .map(|(_, site)| site)
+        .collect()
+}
+
+/// Inserts the random candidates, plus one destination picked at build time for visitors without JS
+fn insert_random_targets(context: &mut Context, candidates: &[&WebringSite], settings: &AppSettings) {
+    let random_url = candidates
+        .choose(&mut rand::thread_rng())
+        .map(|site| site.website.url.clone())
+        .unwrap_or_else(|| settings.base_url.clone());
+    context.insert("random_sites", candidates);
+    context.insert("random_url", &random_url);
+}
+
+/// Copy-paste embed code for a single site, in a few different flavors
+#[derive(Debug, serde::Serialize)]
+pub struct JoinSnippets {
+    pub next_url: String,
+    pub prev_url: String,
+    pub links: String,
+    pub images: String,
+    pub script: String,
+    pub iframe: String,
+    /// The tag proving the site's ownership, if the ring uses ownership tokens
+    pub verification: String,
+}
+
+/// Escapes text for HTML, including inside quoted attributes
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Writes text as a JS string literal, which can't end the `<script>` element it's in
+fn js_string(text: &str) -> String {
+    serde_json::Value::String(text.to_string()).to_string().replace("</", "<\\/")
+}
+
+pub fn build_join_snippets(website: &Website, settings: &AppSettings) -> JoinSnippets {
+    let base_url = settings.base_url.trim_end_matches('/');
+    let next_url = format!("{}/{}/{}", base_url, website.slug, settings.next_url_text);
+    let prev_url = format!("{}/{}/{}", base_url, website.slug, settings.prev_url_text);
+
+    let (prev, base, name, next) = (
+        escape_html(&prev_url),
+        escape_html(base_url),
+        escape_html(&settings.ring_name),
+        escape_html(&next_url),
+    );
+
+    let links = format!(
+        "<a href=\"{}\">&larr;</a>\n<a href=\"{}\">{}</a>\n<a href=\"{}\">&rarr;</a>",
+        prev, base, name, next
+    );
+    // Image buttons expect e.g. 'next.png' & 'previous.png' in the assets folder
+    let images = format!(
+        "<img src=\"{base}/{prev_text}.png\" alt=\"{prev_text}\" onclick=\"{prev_click}\">\n<img src=\"{base}/{next_text}.png\" alt=\"{next_text}\" onclick=\"{next_click}\">",
+        base = base,
+        prev_text = escape_html(&settings.prev_url_text),
+        next_text = escape_html(&settings.next_url_text),
+        prev_click = escape_html(&format!("window.location.href={}", js_string(&prev_url))),
+        next_click = escape_html(&format!("window.location.href={}", js_string(&next_url))),
+    );
+    let markup = format!(
+        "<a href=\"{}\">&larr;</a> <a href=\"{}\">{}</a> <a href=\"{}\">&rarr;</a>",
+        prev, base, name, next
+    );
+    let script = format!(
+        "<script>\ndocument.currentScript.insertAdjacentHTML(\"afterend\", {});\n</script>",
+        js_string(&markup)
+    );
+    // srcdoc holds a whole page, escaped once more to fit in the attribute; single quotes inside keep it readable
+    let srcdoc = format!(
+        "<a href='{}' target='_parent'>&larr;</a> <a href='{}' target='_parent'>{}</a> <a href='{}' target='_parent'>&rarr;</a>",
+        prev, base, name, next
+    );
+    let iframe = format!(
+        "<iframe srcdoc=\"{}\" width=\"300\" height=\"50\" style=\"border:none\"></iframe>",
+        srcdoc.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+    );
+
+    let verification = ownership_token(&website.slug, settings)
+        .map(|token| format!("<meta name=\"{}\" content=\"{}\">", VERIFICATION_META_NAME, token))
+        .unwrap_or_default();
+
+    JoinSnippets {
+        next_url,
+        prev_url,
+        links,
+        images,
+        script,
+        iframe,
+        verification,
+    }
+}
+
 pub async fn build_sites_table_html(websites: &[WebringSite]) -> String {
     // HTML-specific table generation
     let mut table_html = String::new();
@@ -274,7 +744,7 @@ pub async fn build_sites_grid_html(websites: &[WebringSite]) -> String {
     // Layout using CSS grid
     let mut grid_html = String::new();
     grid_html.push_str("<section class=\"cards\">\n");
-    for (_index, website) in websites.iter().enumerate() {
+    for website in websites.iter() {
         grid_html.push_str("<article class=\"card\">\n");
         grid_html.push_str(&format!(
             "<div class=\"card-name\">{} <span class=\"card-slug\">({})</span></div>\n",
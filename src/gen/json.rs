@@ -0,0 +1,72 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::AppSettings;
+use crate::discover::{RingDiscovery, SLUG_PLACEHOLDER, WELL_KNOWN_PATH};
+use crate::error::Error;
+use crate::gen::{webring::WebringSiteList, Generator};
+use crate::website::Website;
+
+/// Where the member list is published, relative to the output folder
+pub const MEMBERS_JSON_PATH: &str = "members.json";
+
+/// Publishes the ring's data as JSON: the member list, and a discovery document describing the ring
+pub struct JsonGenerator;
+
+impl Generator for JsonGenerator {
+    async fn new(_template_path: PathBuf, _skip_minify: bool) -> Result<Self, Error> {
+        Ok(Self)
+    }
+
+    async fn write_content(&self, file_path: &Path, content: &str) -> Result<(), Error> {
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(file_path, content)?;
+        log::info!("Generated JSON file {}", file_path.display());
+        Ok(())
+    }
+
+    async fn generate_content(
+        &self,
+        webring: &WebringSiteList,
+        settings: &AppSettings,
+    ) -> Result<(), Error> {
+        let path_output = Path::new(&settings.path_output);
+
+        let members: Vec<&Website> = webring.sites.iter().map(|site| &site.website).collect();
+        self.write_content(
+            &path_output.join(MEMBERS_JSON_PATH),
+            &serde_json::to_string_pretty(&members)?,
+        )
+        .await?;
+
+        self.write_content(
+            &path_output.join(WELL_KNOWN_PATH),
+            &serde_json::to_string_pretty(&build_discovery_document(webring, settings))?,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Describes this ring for other tools, based on the settings
+pub fn build_discovery_document(webring: &WebringSiteList, settings: &AppSettings) -> RingDiscovery {
+    let base_url = settings.base_url.trim().trim_end_matches('/');
+    let pattern = |url_text: &str| format!("{}/{}/{}", base_url, SLUG_PLACEHOLDER, url_text);
+
+    RingDiscovery {
+        name: settings.ring_name.clone(),
+        description: Some(settings.ring_description.clone()),
+        owner: Some(settings.ring_owner.clone()),
+        owner_site: Some(settings.ring_owner_site.clone()),
+        hub: format!("{}/", base_url),
+        next_url: pattern(&settings.next_url_text),
+        previous_url: pattern(&settings.prev_url_text),
+        random_url: Some(pattern(&settings.random_url_text)),
+        member_count: webring.sites.len(),
+        members: format!("{}/{}", base_url, MEMBERS_JSON_PATH),
+        generator: Some(format!("ringfairy {}", env!("CARGO_PKG_VERSION"))),
+    }
+}
@@ -0,0 +1,79 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::AppSettings;
+use crate::error::Error;
+use crate::gen::{webring::WebringSiteList, Generator};
+
+/// Where the onionring.js-style files are published, relative to the output folder.
+/// These are the names onionring.js uses, so members' existing embeds only need the host changed.
+pub const ONIONRING_VARIABLES_PATH: &str = "onionring-variables.js";
+pub const ONIONRING_WIDGET_PATH: &str = "onionring-widget.js";
+
+const ONIONRING_WIDGET: &str = include_str!("../../data/onionring/onionring-widget.js");
+
+/// Publishes the ring in the format of onionring.js, so sites using its widget can join without changing their embed
+pub struct OnionringGenerator;
+
+impl Generator for OnionringGenerator {
+    async fn new(_template_path: PathBuf, _skip_minify: bool) -> Result<Self, Error> {
+        Ok(Self)
+    }
+
+    async fn write_content(&self, file_path: &Path, content: &str) -> Result<(), Error> {
+        fs::write(file_path, content)?;
+        log::info!("Generated onionring file {}", file_path.display());
+        Ok(())
+    }
+
+    async fn generate_content(
+        &self,
+        webring: &WebringSiteList,
+        settings: &AppSettings,
+    ) -> Result<(), Error> {
+        let path_output = Path::new(&settings.path_output);
+        self.ensure_output_directory(&settings.path_output).await?;
+
+        self.write_content(
+            &path_output.join(ONIONRING_VARIABLES_PATH),
+            &build_onionring_variables(webring, settings),
+        )
+        .await?;
+        self.write_content(&path_output.join(ONIONRING_WIDGET_PATH), ONIONRING_WIDGET)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// The id of the element the onionring widget draws itself in
+pub fn onionring_id(settings: &AppSettings) -> String {
+    match settings.onionring_id.trim() {
+        "" => settings
+            .ring_name
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect(),
+        id => id.to_string(),
+    }
+}
+
+/// Builds onionring-variables.js, listing the members' URLs in ring order
+pub fn build_onionring_variables(webring: &WebringSiteList, settings: &AppSettings) -> String {
+    // JSON strings are valid JS strings, and take care of the escaping
+    let quote = |text: &str| serde_json::Value::String(text.to_string()).to_string();
+    let base_url = settings.base_url.trim();
+
+    let mut variables = String::from("// Generated by ringfairy, in the format of onionring.js's onionring-variables.js\n\n");
+    variables.push_str("var sites = [\n");
+    for site in &webring.sites {
+        variables.push_str(&format!("{},\n", quote(&site.website.url)));
+    }
+    variables.push_str("];\n\n");
+    variables.push_str(&format!("var ringName = {};\n", quote(&settings.ring_name)));
+    variables.push_str(&format!("var ringID = {};\n", quote(&onionring_id(settings))));
+    variables.push_str(&format!("var useIndex = {};\n", !base_url.is_empty()));
+    variables.push_str(&format!("var indexPage = {};\n", quote(base_url)));
+    variables.push_str("var useRandom = true;\n");
+    variables
+}
@@ -0,0 +1,243 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::AppSettings;
+use crate::error::Error;
+use crate::gen::{gemini::gemini_members, webring::WebringSiteList, Generator};
+
+/// Web servers & hosts which can do the next/previous redirects natively
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerKind {
+    Netlify,
+    Apache,
+    Nginx,
+    Caddy,
+    /// A Gemini server, redirecting within the capsule rather than the website
+    Gmid,
+}
+
+impl ServerKind {
+    pub fn from_name(name: &str) -> Result<Self, Error> {
+        match name.trim().to_lowercase().as_str() {
+            "netlify" | "cloudflare" | "_redirects" => Ok(ServerKind::Netlify),
+            "apache" | "htaccess" | ".htaccess" => Ok(ServerKind::Apache),
+            "nginx" => Ok(ServerKind::Nginx),
+            "caddy" | "caddyfile" => Ok(ServerKind::Caddy),
+            "gmid" => Ok(ServerKind::Gmid),
+            other => Err(Error::StringError(format!(
+                "Unknown server config '{}' (expected netlify, apache, nginx, caddy or gmid)",
+                other
+            ))),
+        }
+    }
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ServerKind::Netlify => "_redirects",
+            ServerKind::Apache => ".htaccess",
+            ServerKind::Nginx => "nginx-redirects.conf",
+            ServerKind::Caddy => "Caddyfile.redirects",
+            ServerKind::Gmid => "gmid-redirects.conf",
+        }
+    }
+
+    pub fn is_gemini(&self) -> bool {
+        *self == ServerKind::Gmid
+    }
+}
+
+/// Whether a server config takes care of the next/previous redirects for the website, or for the Gemini capsule
+pub fn has_server_redirects(settings: &AppSettings, gemini: bool) -> bool {
+    settings
+        .server_configs
+        .iter()
+        .filter_map(|name| ServerKind::from_name(name).ok())
+        .any(|kind| kind.is_gemini() == gemini)
+}
+
+pub struct ServerConfigGenerator;
+
+impl Generator for ServerConfigGenerator {
+    async fn new(_template_path: PathBuf, _skip_minify: bool) -> Result<Self, Error> {
+        Ok(Self)
+    }
+
+    async fn write_content(&self, file_path: &Path, content: &str) -> Result<(), Error> {
+        fs::write(file_path, content)?;
+        log::info!("Generated server config {}", file_path.display());
+        Ok(())
+    }
+
+    async fn generate_content(
+        &self,
+        webring: &WebringSiteList,
+        settings: &AppSettings,
+    ) -> Result<(), Error> {
+        self.ensure_output_directory(&settings.path_output).await?;
+
+        for name in &settings.server_configs {
+            let kind = ServerKind::from_name(name)?;
+            // Gemini server configs go with the capsule
+            let path_output = if kind.is_gemini() {
+                if settings.gemini_base_url.trim().is_empty() {
+                    return Err(Error::StringError(format!(
+                        "The {} server config redirects within the Gemini capsule, so it needs gemini_base_url (--gemini-url)",
+                        name
+                    )));
+                }
+                self.ensure_output_directory(&settings.path_output_gemini).await?;
+                &settings.path_output_gemini
+            } else {
+                &settings.path_output
+            };
+            let content = build_server_config(kind, webring, settings);
+            self.write_content(&Path::new(path_output).join(kind.file_name()), &content)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Lists every redirect in the ring as (path, destination), e.g. ("/slug/next", "https://...")
+fn redirect_rules<'a>(
+    kind: ServerKind,
+    webring: &'a WebringSiteList,
+    settings: &AppSettings,
+) -> Vec<(String, &'a str)> {
+    // The capsule is a ring of its own, made of the sites which have a Gemini URL
+    if kind.is_gemini() {
+        let members = gemini_members(webring);
+        let mut rules = Vec::with_capacity(members.len() * 2);
+        for (index, (website, _)) in members.iter().enumerate() {
+            let (_, next) = members[(index + 1) % members.len()];
+            let (_, previous) = members[(index + members.len() - 1) % members.len()];
+            rules.push((format!("/{}/{}", website.slug, settings.next_url_text), next));
+            rules.push((format!("/{}/{}", website.slug, settings.prev_url_text), previous));
+        }
+        return rules;
+    }
+
+    let mut rules = Vec::with_capacity(webring.sites.len() * 2);
+    for site in &webring.sites {
+        rules.push((
+            format!("/{}/{}", site.website.slug, settings.next_url_text),
+            webring.sites[site.next].website.url.as_str(),
+        ));
+        rules.push((
+            format!("/{}/{}", site.website.slug, settings.prev_url_text),
+            webring.sites[site.previous].website.url.as_str(),
+        ));
+    }
+    rules
+}
+
+/// Percent-encodes whitespace & control characters, which no URL may contain as-is
+fn encode_whitespace(url: &str) -> String {
+    let mut encoded = String::with_capacity(url.len());
+    for c in url.chars() {
+        if c.is_whitespace() || c.is_control() {
+            let mut buf = [0; 4];
+            for byte in c.encode_utf8(&mut buf).bytes() {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        } else {
+            encoded.push(c);
+        }
+    }
+    encoded
+}
+
+/// Writes a redirect target as a quoted config argument, encoding or escaping whatever the server would interpret
+fn quote_target(kind: ServerKind, url: &str) -> String {
+    let url = encode_whitespace(url);
+    let escaped = match kind {
+        ServerKind::Netlify => return url,
+        // mod_rewrite reads $N & %N as back-references unless escaped
+        ServerKind::Apache => url
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('$', "\\$")
+            .replace('%', "\\%"),
+        // nginx has no escape for variables, so encode the $
+        ServerKind::Nginx => url
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('$', "%24"),
+        // Caddy reads {...} as placeholders
+        ServerKind::Caddy => url
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('{', "%7B")
+            .replace('}', "%7D"),
+        // gmid reads %p, %q etc. as placeholders
+        ServerKind::Gmid => url
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('%', "%%"),
+    };
+    format!("\"{}\"", escaped)
+}
+
+/// Builds the redirect rules for the given server, ready to be written to its config file
+pub fn build_server_config(
+    kind: ServerKind,
+    webring: &WebringSiteList,
+    settings: &AppSettings,
+) -> String {
+    let rules = redirect_rules(kind, webring, settings);
+    let mut config = format!(
+        "# {} redirects, generated by ringfairy\n",
+        settings.ring_name
+    );
+
+    match kind {
+        ServerKind::Netlify => {
+            for (path, url) in rules {
+                config.push_str(&format!("{} {} 302\n", path, quote_target(kind, url)));
+            }
+        }
+        ServerKind::Apache => {
+            config.push_str("RewriteEngine On\n");
+            for (path, url) in rules {
+                config.push_str(&format!(
+                    "RewriteRule ^{}/?$ {} [R=302,L,NE]\n",
+                    regex::escape(path.trim_start_matches('/')),
+                    quote_target(kind, url)
+                ));
+            }
+        }
+        ServerKind::Nginx => {
+            config.push_str("# Include this inside the ring's server block\n");
+            for (path, url) in rules {
+                config.push_str(&format!(
+                    "location ~ ^{}/?$ {{ return 302 {}; }}\n",
+                    regex::escape(&path),
+                    quote_target(kind, url)
+                ));
+            }
+        }
+        ServerKind::Caddy => {
+            config.push_str("# Import this inside the ring's site block\n");
+            for (path, url) in rules {
+                let url = quote_target(kind, url);
+                config.push_str(&format!("redir {} {} 302\n", path, url));
+                config.push_str(&format!("redir {}/ {} 302\n", path, url));
+            }
+        }
+        ServerKind::Gmid => {
+            config.push_str("# Include this inside the capsule's server block\n");
+            for (path, url) in rules {
+                let url = quote_target(kind, url);
+                for path in [path.clone(), format!("{}/", path)] {
+                    config.push_str(&format!(
+                        "location \"{}\" {{\n\tblock return 30 {}\n}}\n",
+                        path, url
+                    ));
+                }
+            }
+        }
+    }
+
+    config
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::blocklist::Blocklist;
 use crate::cli::AppSettings;
 use crate::gen::html::*;
 use crate::gen::webring::*;
@@ -9,19 +10,6 @@ use std::path::PathBuf;
 
 // Webring
 
-fn create_sample_website(slug: &str, url: &str) -> Website {
-    Website {
-        slug: slug.to_string(),
-        name: Some(format!("Site {}", slug)),
-        about: Some(format!("About {}", slug)),
-        url: url.to_string(),
-        rss: Some(format!("http://{}.tld/rss", slug)),
-        atom: Some(format!("http://{}.tld/atom.xml", slug)),
-        owner: Some(format!("Owner {}", slug)),
-        misc: None,
-    }
-}
-
 fn build_settings() -> AppSettings {
     AppSettings {
         shuffle: false,
@@ -40,9 +28,9 @@ fn build_settings_no_shuffle() -> AppSettings {
 async fn test_build_webring() {
     // sample data
     let websites = vec![
-        create_sample_website("site1", "https://site1.tld"),
-        create_sample_website("site2", "https://site2.tld"),
-        create_sample_website("site3", "https://site3.tld"),
+        Website::sample("site1", "https://site1.tld"),
+        Website::sample("site2", "https://site2.tld"),
+        Website::sample("site3", "https://site3.tld"),
     ];
 
     let webring_sites = build_webring_sequence(websites.clone(), &build_settings()).await;
@@ -66,9 +54,9 @@ async fn test_build_webring() {
 async fn test_build_webring_shuffle() {
     // sample data
     let websites = vec![
-        create_sample_website("site1", "https://site1.tld"),
-        create_sample_website("site2", "https://site2.tld"),
-        create_sample_website("site3", "https://site3.tld"),
+        Website::sample("site1", "https://site1.tld"),
+        Website::sample("site2", "https://site2.tld"),
+        Website::sample("site3", "https://site3.tld"),
     ];
 
     // shuffle enabled
@@ -98,56 +86,226 @@ async fn test_build_webring_shuffle() {
     }
 }
 
+#[tokio::test]
+async fn test_build_webring_numbers_slugs_in_ring_order() {
+    let websites: Vec<Website> = (1..=10)
+        .map(|n| Website::sample("", &format!("https://site{}.tld", n)))
+        .collect();
+    let settings = AppSettings {
+        shuffle: true,
+        no_slug: true,
+        ..AppSettings::default()
+    };
+
+    // Provisional numbers from the list order are replaced once the ring is shuffled
+    let prepared = prepare_websites(websites, &Blocklist::default(), &settings).unwrap();
+    let webring_sites = build_webring_sequence(prepared, &settings).await;
+    for (index, site) in webring_sites.iter().enumerate() {
+        assert_eq!(site.website.slug, (index + 1).to_string());
+    }
+
+    let settings = AppSettings {
+        require_verified: true,
+        verification_secret: "secret".to_string(),
+        ..settings
+    };
+    assert!(prepare_websites(Vec::new(), &Blocklist::default(), &settings).is_err());
+}
+
 #[tokio::test]
 async fn test_verify_websites_valid() {
     let websites = vec![
-        create_sample_website("site1", "https://site1.tld"),
-        create_sample_website("site2", "https://site2.tld"),
-        create_sample_website("site3", "https://site3.tld"),
+        Website::sample("site1", "https://site1.tld"),
+        Website::sample("site2", "https://site2.tld"),
+        Website::sample("site3", "https://site3.tld"),
     ];
 
-    let result = verify_websites(&websites);
+    let result = verify_websites(&websites, &Blocklist::default());
     assert!(result.is_ok());
 }
 
 #[tokio::test]
 async fn test_verify_duplicate_slugs() {
     let websites = vec![
-        create_sample_website("site1", "https://site1.tld"),
-        create_sample_website("site1", "https://site2.tld"),
+        Website::sample("site1", "https://site1.tld"),
+        Website::sample("site1", "https://site2.tld"),
     ];
 
-    let result = verify_websites(&websites);
+    let result = verify_websites(&websites, &Blocklist::default());
     assert!(result.is_err());
 }
 
 #[tokio::test]
 async fn test_verify_duplicate_urls() {
     let websites = vec![
-        create_sample_website("site1", "https://site1.tld"),
-        create_sample_website("site2", "https://site1.tld"),
+        Website::sample("site1", "https://site1.tld"),
+        Website::sample("site2", "https://site1.tld"),
     ];
 
-    let result = verify_websites(&websites);
+    let result = verify_websites(&websites, &Blocklist::default());
     assert!(result.is_err());
 }
 
 #[tokio::test]
 async fn test_verify_empty_url() {
-    let websites = vec![create_sample_website("site1", "")];
+    let websites = vec![Website::sample("site1", "")];
 
-    let result = verify_websites(&websites);
+    let result = verify_websites(&websites, &Blocklist::default());
     assert!(result.is_err());
 }
 
 #[tokio::test]
 async fn test_verify_invalid_url() {
-    let websites = vec![create_sample_website("site1", "htp/invalid-url")];
+    let websites = vec![Website::sample("site1", "htp/invalid-url")];
+
+    let result = verify_websites(&websites, &Blocklist::default());
+    assert!(result.is_err());
+}
 
-    let result = verify_websites(&websites);
+#[tokio::test]
+async fn test_verify_invalid_gemini_url() {
+    let mut website = Website::sample("site1", "https://site1.tld");
+    website.gemini_url = Some("https://site1.tld".to_string());
+
+    let result = verify_websites(&[website], &Blocklist::default());
     assert!(result.is_err());
 }
 
+#[tokio::test]
+async fn test_verify_blocked_site() {
+    let blocklist = Blocklist::parse("spam.tld # keeps coming back", "blocklist.txt").unwrap();
+    let websites = vec![
+        Website::sample("site1", "https://site1.tld"),
+        Website::sample("new-slug", "https://www.spam.tld/"),
+    ];
+
+    let result = verify_websites(&websites, &blocklist);
+    let message = result.unwrap_err().to_string();
+    assert!(message.contains("https://www.spam.tld/"));
+    assert!(message.contains("keeps coming back"));
+}
+
+#[tokio::test]
+async fn test_split_by_status() {
+    let day = |d: u32| chrono::NaiveDate::from_ymd_opt(2024, 6, d).unwrap();
+    let site = |slug: &str, status: Option<MemberStatus>, from: Option<u32>, until: Option<u32>| Website {
+        status,
+        active_from: from.map(day),
+        active_until: until.map(day),
+        ..Website::sample(slug, &format!("https://{}.tld", slug))
+    };
+    let websites = vec![
+        site("active", None, None, None),
+        site("paused", Some(MemberStatus::Paused), None, None),
+        site("hidden", Some(MemberStatus::Hidden), None, None),
+        site("pending", Some(MemberStatus::Pending), None, None),
+        site("scheduled", Some(MemberStatus::Active), Some(10), Some(20)),
+    ];
+    let slugs = |sites: &[Website]| sites.iter().map(|w| w.slug.clone()).collect::<Vec<_>>();
+
+    let (active, paused, upcoming) = split_by_status(websites.clone(), day(1));
+    assert_eq!(slugs(&active), vec!["active"]);
+    assert_eq!(slugs(&paused), vec!["paused"]);
+    assert_eq!(slugs(&upcoming), vec!["scheduled"]);
+
+    // Scheduled sites join on their first day, and leave after their last
+    let (active, _, upcoming) = split_by_status(websites.clone(), day(10));
+    assert_eq!(slugs(&active), vec!["active", "scheduled"]);
+    assert!(upcoming.is_empty());
+    let (active, _, upcoming) = split_by_status(websites.clone(), day(21));
+    assert_eq!(slugs(&active), vec!["active"]);
+    assert!(upcoming.is_empty());
+
+    // The sequence itself never includes inactive sites
+    let webring_sites = build_webring_sequence(websites, &build_settings()).await;
+    assert_eq!(webring_sites.len(), 1);
+    assert_eq!(webring_sites[0].next, 0);
+}
+
+#[tokio::test]
+async fn test_renamed_redirect_template() {
+    let dir = std::env::temp_dir().join(format!("ringfairy-renamed-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("templates")).unwrap();
+    std::fs::copy("./data/templates/redirect.html", dir.join("templates/hop.html")).unwrap();
+    std::fs::write(dir.join("templates/about.html"), "<p>{{ ring_name }}</p>").unwrap();
+
+    let list = dir.join("websites.json");
+    let websites = vec![
+        Website::sample("site1", "https://site1.tld"),
+        Website::sample("site2", "https://site2.tld"),
+    ];
+    std::fs::write(&list, serde_json::to_string(&websites).unwrap()).unwrap();
+    let settings = AppSettings {
+        filepath_list: vec![list.to_string_lossy().to_string()],
+        path_output: dir.join("out").to_string_lossy().to_string(),
+        path_templates: dir.join("templates").to_string_lossy().to_string(),
+        filename_template_redirect: "hop.html".to_string(),
+        ..mock_app_settings()
+    };
+    generate_webring_files(&settings).await.unwrap();
+
+    let out = dir.join("out");
+    assert!(out.join("site1/next/index.html").exists());
+    assert!(out.join("about.html").exists());
+    for page in ["index.html", "members.html", "site1/index.html", "site1/join.html"] {
+        assert!(out.join(page).exists(), "Built-in {} should fill in the gap", page);
+    }
+    for page in ["hop.html", "redirect.html", "member.html"] {
+        assert!(!out.join(page).exists(), "{} shouldn't be generated", page);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_private_fields_not_generated() {
+    let dir = std::env::temp_dir().join(format!("ringfairy-private-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut websites = vec![
+        Website::sample("site1", "https://site1.tld"),
+        Website::sample("site2", "https://site2.tld"),
+    ];
+    websites[0].private = Some(serde_json::json!({ "email": "secret@site1.tld", "notes": "admin note" }));
+    websites[1].misc = Some(serde_json::json!({ "email": "secret@site2.tld", "tags": ["art"] }));
+    let public = websites[1].clone().into_public(&["email".to_string()]);
+    assert_eq!(public.misc, Some(serde_json::json!({ "tags": ["art"] })));
+
+    let list = dir.join("websites.json");
+    std::fs::write(&list, serde_json::to_string(&websites).unwrap()).unwrap();
+    let settings = AppSettings {
+        filepath_list: vec![list.to_string_lossy().to_string()],
+        path_output: dir.join("out").to_string_lossy().to_string(),
+        path_templates: "./data/templates".to_string(),
+        private_fields: vec!["email".to_string()],
+        onionring: true,
+        ..mock_app_settings()
+    };
+    generate_webring_files(&settings).await.unwrap();
+
+    let mut folders = vec![dir.join("out")];
+    let mut checked = 0;
+    while let Some(folder) = folders.pop() {
+        for entry in std::fs::read_dir(folder).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                folders.push(path);
+            } else if let Ok(content) = std::fs::read_to_string(&path) {
+                assert!(!content.contains("secret@"), "{} has a private email", path.display());
+                assert!(!content.contains("admin note"), "{} has a private note", path.display());
+                checked += 1;
+            }
+        }
+    }
+    assert!(checked > 0);
+
+    // The list itself keeps them, for admins
+    let kept = crate::file::parse_website_list(&settings).await.unwrap();
+    assert!(kept[0].private.is_some());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
 // HTML
 
 // Mock data
@@ -163,7 +321,7 @@ fn mock_webring_site() -> WebringSiteList {
                     owner: Some("owner1".to_string()),
                     rss: Some("https://site1.com/rss".to_string()),
                     atom: Some("https://site1.com/atom.xml".to_string()),
-                    misc: None,
+                    ..Default::default()
                 },
                 previous: 1,
                 next: 1,
@@ -177,13 +335,15 @@ fn mock_webring_site() -> WebringSiteList {
                     owner: Some("owner2".to_string()),
                     rss: Some("https://site2.com/rss".to_string()),
                     atom: Some("https://site2.com/atom.xml".to_string()),
-                    misc: None,
+                    ..Default::default()
                 },
                 previous: 0,
                 next: 0,
             },
         ],
         failed_sites: vec![], 
+        paused_sites: vec![],
+        upcoming_sites: vec![],
     }
 }
 
@@ -239,7 +399,7 @@ async fn test_ensure_output_directory() {
 async fn test_audit_websites() {
     // Mock settings and website
     let settings = mock_app_settings();
-    let mut mock_site = create_sample_website("test", "");
+    let mut mock_site = Website::sample("test", "");
 
     // Mock HTTP server and client
     let mut mock_server = mockito::Server::new_async().await; // mockito server
@@ -264,3 +424,541 @@ async fn test_audit_websites() {
     // TODO call audit function website::audit_links
     //        -> verify function returns correctly audited sites
 }
+
+#[tokio::test]
+async fn test_listed_provenance_does_not_skip_audit() {
+    let settings = mock_app_settings();
+    let mut mock_server = mockito::Server::new_async().await;
+    let mock = mock_server
+        .mock("GET", "/")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body("<p>No ring links here</p>")
+        .create();
+
+    let list = format!(
+        r#"[{{"slug": "test", "url": "{}", "provenance": {{"ring_name": "Other", "ring_hub": "https://other.tld/", "slug": "test", "is_ring": false}}}}]"#,
+        mock_server.url()
+    );
+    let websites = crate::file::parse_website_file("websites.json", &list).unwrap();
+    assert!(websites[0].provenance.is_none());
+
+    let audit_client = http::setup_client(&settings).await.unwrap();
+    let audited = website::audit_links(&audit_client, websites, &settings).await.unwrap();
+
+    mock.assert_async().await;
+    assert!(audited.is_empty(), "A hand-written provenance mustn't skip the audit");
+}
+
+#[tokio::test]
+async fn test_build_join_snippets() {
+    let settings = mock_app_settings();
+
+    let snippets = build_join_snippets(&Website::sample("site1", "https://site1.tld"), &settings);
+
+    assert_eq!(snippets.next_url, "https://example.com/site1/next");
+    assert_eq!(snippets.prev_url, "https://example.com/site1/prev");
+    for snippet in [&snippets.links, &snippets.images, &snippets.script] {
+        assert!(snippet.contains(&snippets.next_url));
+        assert!(snippet.contains(&snippets.prev_url));
+    }
+    assert!(snippets.iframe.contains(&snippets.next_url));
+    assert!(snippets.verification.is_empty(), "No token without a secret");
+}
+
+#[tokio::test]
+async fn test_build_join_snippets_escapes_ring_name() {
+    let mut settings = mock_app_settings();
+    settings.ring_name = "O'Brien \"Ring\" </script><b>".to_string();
+
+    let snippets = build_join_snippets(&Website::sample("site1", "https://site1.tld"), &settings);
+
+    let link_text = |html: &str| {
+        let document = scraper::Html::parse_fragment(html);
+        let selector = scraper::Selector::parse("a").unwrap();
+        let texts: Vec<String> = document.select(&selector).map(|a| a.text().collect()).collect();
+        texts[1].clone()
+    };
+    assert_eq!(link_text(&snippets.links), settings.ring_name);
+    assert_eq!(snippets.script.matches("</script>").count(), 1);
+    assert!(!snippets.script.contains("<b>"));
+
+    let document = scraper::Html::parse_fragment(&snippets.iframe);
+    let iframe = document.select(&scraper::Selector::parse("iframe").unwrap()).next().unwrap();
+    assert_eq!(link_text(iframe.value().attr("srcdoc").unwrap()), settings.ring_name);
+}
+
+#[tokio::test]
+async fn test_random_candidates_exclude_origin() {
+    let webring = mock_webring_site();
+    let mut settings = mock_app_settings();
+
+    let all = random_candidates(&webring, None, &settings);
+    assert_eq!(all.len(), 2);
+
+    let from_site1 = random_candidates(&webring, Some(0), &settings);
+    assert_eq!(from_site1.len(), 1);
+    assert_eq!(from_site1[0].website.slug, "site2");
+
+    settings.random_exclude_neighbors = true;
+    assert!(random_candidates(&webring, Some(0), &settings).is_empty());
+
+    settings.random_exclude_neighbors = false;
+    settings.random_exclude = vec!["site2".to_string()];
+    let all = random_candidates(&webring, None, &settings);
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0].website.slug, "site1");
+}
+
+#[tokio::test]
+async fn test_canonicalize_url() {
+    assert_eq!(canonicalize_url("https://www.Site1.com/"), "site1.com");
+    assert_eq!(canonicalize_url("http://site1.com/blog//"), "site1.com/blog");
+    assert_eq!(canonicalize_url("https://site1.com:8080/~me/"), "site1.com:8080/~me");
+    assert_eq!(
+        canonicalize_url("https://site1.com/blog?page=2#top"),
+        canonicalize_url("http://www.site1.com/blog/")
+    );
+}
+
+#[tokio::test]
+async fn test_audit_websites_universal_links() {
+    let mut settings = mock_app_settings();
+    let mut mock_site = Website::sample("test", "");
+
+    let mut mock_server = mockito::Server::new_async().await;
+    mock_site.url = mock_server.url();
+    let mock = mock_server
+        .mock("GET", "/")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(
+            r#"<a href="https://example.com/prev/">←</a>
+    <a href="https://example.com/">Test Ring</a>
+    <a href="https://example.com/next/">→</a>"#,
+        )
+        .expect(2)
+        .create();
+    let audit_client = http::setup_client(&settings).await.unwrap();
+
+    let (_, passed, _) = website::does_html_contain_links(&audit_client, &mock_site, &settings)
+        .await
+        .unwrap();
+    assert!(!passed, "Universal links shouldn't pass unless enabled");
+
+    settings.universal_links = true;
+    let (_, passed, _) = website::does_html_contain_links(&audit_client, &mock_site, &settings)
+        .await
+        .unwrap();
+    assert!(passed);
+
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_build_server_configs() {
+    use crate::gen::server::*;
+
+    let webring = mock_webring_site();
+    let settings = mock_app_settings();
+
+    let netlify = build_server_config(ServerKind::Netlify, &webring, &settings);
+    assert!(netlify.contains("/site1/next https://site2.com 302"));
+    assert!(netlify.contains("/site2/prev https://site1.com 302"));
+
+    let apache = build_server_config(ServerKind::Apache, &webring, &settings);
+    assert!(apache.contains("RewriteRule ^site1/next/?$ \"https://site2.com\" [R=302,L,NE]"));
+
+    let nginx = build_server_config(ServerKind::Nginx, &webring, &settings);
+    assert!(nginx.contains("location ~ ^/site1/prev/?$ { return 302 \"https://site2.com\"; }"));
+
+    let caddy = build_server_config(ServerKind::Caddy, &webring, &settings);
+    assert!(caddy.contains("redir /site2/next/ \"https://site1.com\" 302"));
+
+    assert!(ServerKind::from_name("Cloudflare").is_ok());
+    assert!(ServerKind::from_name("iis").is_err());
+}
+
+#[tokio::test]
+async fn test_build_server_configs_escape_targets() {
+    use crate::gen::server::*;
+
+    let mut webring = mock_webring_site();
+    webring.sites[1].website.url = "https://site2.com/a%20b;c $1 {x}".to_string();
+    let settings = mock_app_settings();
+
+    let netlify = build_server_config(ServerKind::Netlify, &webring, &settings);
+    assert!(netlify.contains("/site1/next https://site2.com/a%20b;c%20$1%20{x} 302"));
+
+    let apache = build_server_config(ServerKind::Apache, &webring, &settings);
+    assert!(apache.contains(
+        "RewriteRule ^site1/next/?$ \"https://site2.com/a\\%20b;c\\%20\\$1\\%20{x}\" [R=302,L,NE]"
+    ));
+
+    let nginx = build_server_config(ServerKind::Nginx, &webring, &settings);
+    assert!(nginx.contains(
+        "location ~ ^/site1/next/?$ { return 302 \"https://site2.com/a%20b;c%20%241%20{x}\"; }"
+    ));
+
+    let caddy = build_server_config(ServerKind::Caddy, &webring, &settings);
+    assert!(caddy.contains("redir /site1/next \"https://site2.com/a%20b;c%20$1%20%7Bx%7D\" 302"));
+}
+
+#[tokio::test]
+async fn test_build_gmid_config() {
+    use crate::gen::server::*;
+
+    let mut webring = mock_webring_site();
+    webring.sites[0].website.gemini_url = Some("gemini://site1.com/".to_string());
+    webring.sites[1].website.gemini_url = Some("gemini://site2.com/100%25".to_string());
+    let mut settings = mock_app_settings();
+
+    let gmid = build_server_config(ServerKind::Gmid, &webring, &settings);
+    assert!(gmid.contains("location \"/site1/next/\" {\n\tblock return 30 \"gemini://site2.com/100%%25\"\n}\n"));
+    assert!(gmid.contains("location \"/site2/prev\" {\n\tblock return 30 \"gemini://site1.com/\"\n}\n"));
+    assert!(!gmid.contains("https://"), "Only Gemini URLs: {}", gmid);
+
+    settings.server_configs = vec!["gmid".to_string()];
+    assert!(has_server_redirects(&settings, true));
+    assert!(!has_server_redirects(&settings, false));
+}
+
+#[tokio::test]
+async fn test_build_gemini_hub() {
+    use crate::gen::gemini::*;
+
+    let mut webring = mock_webring_site();
+    let mut settings = mock_app_settings();
+    settings.gemini_base_url = "gemini://example.com/".to_string();
+    webring.sites[1].website.gemini_url = Some("gemini://site2.com".to_string());
+
+    let members = gemini_members(&webring);
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].0.slug, "site2");
+
+    let hub = build_gemini_hub(&members, &settings);
+    assert!(hub.starts_with("# Test Ring\n"));
+    assert!(hub.contains("=> gemini://site2.com Site 2\nAbout Site 2\n"));
+    assert!(hub.contains("gemini://example.com/<slug>/next/"));
+}
+
+#[tokio::test]
+async fn test_build_gopher_hub() {
+    use crate::gen::gopher::*;
+
+    let mut webring = mock_webring_site();
+    let mut settings = mock_app_settings();
+    settings.gopher_base_url = "gopher://example.com/1/ring".to_string();
+    webring.sites[0].website.gopher_url = Some("gopher://site1.com:7070/1/hole".to_string());
+    webring.sites[0].website.about = Some("Line one\r\nline\ttwo".to_string());
+
+    let members = gopher_members(&webring);
+    assert_eq!(members.len(), 1);
+    assert_eq!(
+        members[0].1,
+        GopherItem {
+            item_type: '1',
+            selector: "/hole".to_string(),
+            host: "site1.com".to_string(),
+            port: 7070,
+        }
+    );
+
+    let base = GopherItem::from_url(&settings.gopher_base_url).unwrap();
+    let hub = build_gopher_hub(&members, &base, &settings);
+    assert!(hub.starts_with("iTest Ring\tfake\t(NULL)\t0\n"));
+    assert!(hub.contains("1Site 1\t/hole\tsite1.com\t7070\n"));
+    assert!(hub.contains("1  next\t/ring/site1/next\texample.com\t70\n"));
+    assert!(hub.contains("iLine one  line two\tfake\t(NULL)\t0\n"));
+    assert!(hub.contains(
+        "iMembers link to the menus /ring/<slug>/next and /ring/<slug>/prev on example.com:70 from their gopher hole.\tfake"
+    ));
+    assert!(hub.lines().all(|line| line.split('\t').count() == 4), "{}", hub);
+    assert!(GopherItem::from_url("https://site1.com").is_none());
+}
+
+#[tokio::test]
+async fn test_seo_files() {
+    let settings = mock_app_settings();
+
+    let pages = ["index.html".to_string(), "list.html".to_string(), "feed.xml".to_string()];
+    let sitemap = build_sitemap_xml(&pages, &settings);
+    assert!(sitemap.contains("<loc>https://example.com/</loc>"));
+    assert!(sitemap.contains("<loc>https://example.com/list.html</loc>"));
+    assert!(!sitemap.contains("feed.xml"));
+
+    // Redirect pages aren't disallowed, or crawlers would never see their noindex
+    let robots = build_robots_txt(&settings);
+    assert!(robots.starts_with("User-agent: *\nDisallow:\n"));
+    assert!(!robots.contains("Disallow: /"));
+    assert!(robots.contains("Sitemap: https://example.com/sitemap.xml"));
+
+    let page = inject_head_tags("<html><head><title>Hi</title></head></html>", "<meta>");
+    assert_eq!(page, "<html><head><title>Hi</title><meta></head></html>");
+}
+
+#[tokio::test]
+async fn test_build_discovery_document() {
+    use crate::gen::json::build_discovery_document;
+
+    let webring = mock_webring_site();
+    let settings = mock_app_settings();
+
+    let document = build_discovery_document(&webring, &settings);
+    assert_eq!(document.hub, "https://example.com/");
+    assert_eq!(document.next_url, "https://example.com/{slug}/next");
+    assert_eq!(document.members, "https://example.com/members.json");
+    assert_eq!(document.member_count, 2);
+    assert!(document.problems().is_empty());
+}
+
+#[tokio::test]
+async fn test_builtin_templates_and_themes() {
+    use crate::gen::theme::*;
+
+    // Every missing template falls back to the built-in one, whether or not there's a templates folder
+    let generator = HtmlGenerator::new(PathBuf::from("no_such_templates"), false).await;
+    assert!(generator.is_ok());
+    let settings = AppSettings {
+        filename_template_redirect: "hop.html".to_string(),
+        ..build_settings()
+    };
+    let names = |templates: Vec<(String, &str)>| templates.into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+    let fallbacks = names(fallback_templates(&settings));
+    assert!(fallbacks.contains(&"hop.html".to_string()));
+    assert!(!fallbacks.contains(&"redirect.html".to_string()));
+    assert!(fallbacks.contains(&"member.html".to_string()));
+    assert!(fallbacks.contains(&"grid.html".to_string()));
+
+    let settings = AppSettings {
+        skip_builtin_templates: true,
+        ..settings
+    };
+    assert!(fallback_templates(&settings).is_empty());
+
+    assert!(theme_stylesheet("default").is_ok());
+    assert!(theme_stylesheet("Terminal").is_ok());
+    assert!(theme_stylesheet("no-such-theme").is_err());
+}
+
+#[tokio::test]
+async fn test_build_onionring_variables() {
+    use crate::gen::onionring::*;
+
+    let webring = mock_webring_site();
+    let mut settings = mock_app_settings();
+
+    let variables = build_onionring_variables(&webring, &settings);
+    assert!(variables.contains("var sites = [\n\"https://site1.com\",\n\"https://site2.com\",\n];"));
+    assert!(variables.contains("var ringName = \"Test Ring\";"));
+    assert!(variables.contains("var ringID = \"TestRing\";"));
+    assert!(variables.contains("var indexPage = \"https://example.com\";"));
+
+    settings.onionring_id = "my-ring".to_string();
+    assert_eq!(onionring_id(&settings), "my-ring");
+}
+
+#[tokio::test]
+async fn test_audit_websites_onionring() {
+    let mut settings = mock_app_settings();
+    let mut mock_site = Website::sample("test", "");
+
+    let mut mock_server = mockito::Server::new_async().await;
+    mock_site.url = mock_server.url();
+    let embed = |variables_src: &str, widget_src: &str| {
+        format!(
+            r#"<div id="TestRing"></div>
+    <script type="text/javascript" src="{}"></script>
+    <script type="text/javascript" src="{}"></script>"#,
+            variables_src, widget_src
+        )
+    };
+    let ours = mock_server
+        .mock("GET", "/")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(embed("https://example.com/onionring-variables.js?v=2", "https://EXAMPLE.com/onionring-widget.js"))
+        .expect(2)
+        .create();
+    let audit_client = http::setup_client(&settings).await.unwrap();
+
+    let (_, passed, _) = website::does_html_contain_links(&audit_client, &mock_site, &settings)
+        .await
+        .unwrap();
+    assert!(!passed, "The onionring widget shouldn't pass unless enabled");
+
+    settings.onionring = true;
+    let (_, passed, _) = website::does_html_contain_links(&audit_client, &mock_site, &settings)
+        .await
+        .unwrap();
+    assert!(passed);
+    ours.assert_async().await;
+
+    // Another ring's scripts don't count
+    ours.remove_async().await;
+    for (variables, widget) in [
+        ("https://other-ring.tld/onionring-variables.js", "https://example.com/onionring-widget.js"),
+        ("https://example.com/onionring-variables.js", "https://other-ring.tld/onionring-widget.js"),
+        ("https://example.com/onionring-variables.js", "/js/onionring-widget.js"),
+    ] {
+        let other = mock_server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_header("content-type", "text/html")
+            .with_body(embed(variables, widget))
+            .create();
+        let (_, passed, _) = website::does_html_contain_links(&audit_client, &mock_site, &settings)
+            .await
+            .unwrap();
+        assert!(!passed, "{} & {}", variables, widget);
+        other.remove_async().await;
+    }
+
+    // Without a base URL, there's no telling whose embed it is
+    settings.base_url = String::new();
+    let any = mock_server
+        .mock("GET", "/")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(embed("https://other-ring.tld/onionring-variables.js", "https://other-ring.tld/onionring-widget.js"))
+        .create();
+    let (_, passed, _) = website::does_html_contain_links(&audit_client, &mock_site, &settings)
+        .await
+        .unwrap();
+    assert!(!passed);
+    any.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_audit_ownership_verification() {
+    let mut settings = mock_app_settings();
+    let mut mock_server = mockito::Server::new_async().await;
+    let mock_site = Website::sample("test", &mock_server.url());
+    let links = r#"<a href="https://example.com/test/next">next</a><a href="https://example.com/test/prev">prev</a>"#;
+    let audit_client = http::setup_client(&settings).await.unwrap();
+
+    let page = mock_server
+        .mock("GET", "/")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(links)
+        .create();
+    let (audited, passed, _) = website::does_html_contain_links(&audit_client, &mock_site, &settings)
+        .await
+        .unwrap();
+    assert!(passed);
+    assert_eq!(audited.verified, None, "Nothing to check without a secret");
+
+    settings.verification_secret = "secret".to_string();
+    let token = ownership_token(&mock_site.slug, &settings).unwrap();
+    assert_eq!(token.len(), 32);
+    assert_ne!(ownership_token("other", &settings), Some(token.clone()));
+    assert!(build_join_snippets(&mock_site, &settings).verification.contains(&token));
+
+    // Links alone pass the audit, but don't verify ownership
+    let (audited, passed, _) = website::does_html_contain_links(&audit_client, &mock_site, &settings)
+        .await
+        .unwrap();
+    assert!(passed);
+    assert_eq!(audited.verified, Some(false));
+    let compliant = audit_links(&audit_client, vec![mock_site.clone()], &settings).await.unwrap();
+    assert_eq!(compliant.len(), 1);
+    settings.require_verified = true;
+    let compliant = audit_links(&audit_client, vec![mock_site.clone()], &settings).await.unwrap();
+    assert!(compliant.is_empty(), "Unverified sites are left out when required");
+
+    // The token in a meta tag...
+    page.remove_async().await;
+    let page = mock_server
+        .mock("GET", "/")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(format!(r#"<meta name="webring-verification" content="{}">{}"#, token, links))
+        .create();
+    let compliant = audit_links(&audit_client, vec![mock_site.clone()], &settings).await.unwrap();
+    assert_eq!(compliant[0].verified, Some(true));
+
+    // ...or in the site's verification file
+    page.remove_async().await;
+    mock_server
+        .mock("GET", "/")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(links)
+        .create();
+    mock_server
+        .mock("GET", "/.well-known/webring-verification")
+        .with_status(200)
+        .with_body(format!("some-other-ring-token\n{}\n", token))
+        .create();
+    let (audited, _, _) = website::does_html_contain_links(&audit_client, &mock_site, &settings)
+        .await
+        .unwrap();
+    assert_eq!(audited.verified, Some(true));
+}
+
+#[tokio::test]
+async fn test_build_verifies_slugless_member() {
+    let dir = std::env::temp_dir().join(format!("ringfairy-slugless-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut mock_server = mockito::Server::new_async().await;
+    let member = Website::sample("", &mock_server.url());
+    let settings = AppSettings {
+        filepath_list: vec![dir.join("websites.json").to_string_lossy().to_string()],
+        path_output: dir.join("out").to_string_lossy().to_string(),
+        path_templates: "./data/templates".to_string(),
+        audit: true,
+        verification_secret: "secret".to_string(),
+        require_verified: true,
+        ..mock_app_settings()
+    };
+    std::fs::write(&settings.filepath_list[0], serde_json::to_string(&vec![member.clone()]).unwrap()).unwrap();
+
+    // The member copies the code from their join page, which has the slug filled in
+    let mut slugged = vec![member];
+    assign_slugs(&mut slugged, false);
+    let snippets = build_join_snippets(&slugged[0], &settings);
+    mock_server
+        .mock("GET", "/")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(format!("{}{}", snippets.verification, snippets.links))
+        .create();
+
+    generate_webring_files(&settings).await.unwrap();
+    assert!(dir.join("out").join(&slugged[0].slug).join("next/index.html").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_listed_verified_is_not_trusted() {
+    let dir = std::env::temp_dir().join(format!("ringfairy-listed-verified-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let list = dir.join("websites.json");
+    std::fs::write(
+        &list,
+        r#"[{"slug": "site1", "url": "https://site1.tld", "verified": true},
+            {"slug": "site2", "url": "https://site2.tld", "verified": true}]"#,
+    )
+    .unwrap();
+    let websites = crate::file::parse_website_file("websites.json", &std::fs::read_to_string(&list).unwrap()).unwrap();
+    assert!(websites.iter().all(|website| website.verified.is_none()));
+
+    // Without the audit, nothing has been verified
+    let settings = AppSettings {
+        filepath_list: vec![list.to_string_lossy().to_string()],
+        path_output: dir.join("out").to_string_lossy().to_string(),
+        path_templates: "./data/templates".to_string(),
+        audit: false,
+        verification_secret: "secret".to_string(),
+        require_verified: true,
+        ..mock_app_settings()
+    };
+    assert!(generate_webring_files(&settings).await.is_err());
+    assert!(!dir.join("out").join("site1").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
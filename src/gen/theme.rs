@@ -0,0 +1,78 @@
+use std::fs;
+use std::path::Path;
+
+use crate::cli::AppSettings;
+use crate::error::Error;
+
+/// Templates bundled into the binary, as written out by `ringfairy init`
+pub const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
+    ("grid.html", include_str!("../../data/templates/grid.html")),
+    ("index.html", include_str!("../../data/templates/index.html")),
+    ("join.html", include_str!("../../data/templates/join.html")),
+    ("member.html", include_str!("../../data/templates/member.html")),
+    ("members.html", include_str!("../../data/templates/members.html")),
+    ("random.html", include_str!("../../data/templates/random.html")),
+    ("redirect.html", include_str!("../../data/templates/redirect.html")),
+    ("universal.html", include_str!("../../data/templates/universal.html")),
+];
+
+fn default_template(name: &str) -> &'static str {
+    DEFAULT_TEMPLATES
+        .iter()
+        .find(|(default_name, _)| *default_name == name)
+        .map(|(_, content)| *content)
+        .unwrap_or_default()
+}
+
+/// The built-in templates to fall back on for anything missing from the templates folder, as (name, template).
+/// The special templates are named the way the settings expect. None at all with `skip_builtin_templates`.
+pub fn fallback_templates(settings: &AppSettings) -> Vec<(String, &'static str)> {
+    if settings.skip_builtin_templates {
+        return Vec::new();
+    }
+    vec![
+        (settings.filename_template_redirect.clone(), default_template("redirect.html")),
+        (settings.filename_template_random.clone(), default_template("random.html")),
+        (settings.filename_template_universal.clone(), default_template("universal.html")),
+        (settings.filename_template_join.clone(), default_template("join.html")),
+        (settings.filename_template_member.clone(), default_template("member.html")),
+        ("index.html".to_string(), default_template("index.html")),
+        ("grid.html".to_string(), default_template("grid.html")),
+        ("members.html".to_string(), default_template("members.html")),
+    ]
+}
+
+/// Built-in themes, as (name, stylesheet)
+pub const THEMES: &[(&str, &str)] = &[
+    ("default", include_str!("../../data/themes/default/styles.css")),
+    ("light", include_str!("../../data/themes/light/styles.css")),
+    ("terminal", include_str!("../../data/themes/terminal/styles.css")),
+];
+
+/// Looks up a built-in theme's stylesheet by name
+pub fn theme_stylesheet(theme: &str) -> Result<&'static str, Error> {
+    THEMES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(theme.trim()))
+        .map(|(_, stylesheet)| *stylesheet)
+        .ok_or_else(|| {
+            Error::StringError(format!(
+                "Unknown theme '{}' (available: {})",
+                theme,
+                THEMES
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })
+}
+
+/// Writes the theme's stylesheet into the output folder. Anything in the assets folder gets copied afterwards, so it can override this.
+pub fn write_theme_assets(theme: &str, output_dir: &str) -> Result<(), Error> {
+    let stylesheet = theme_stylesheet(theme)?;
+    fs::create_dir_all(output_dir)?;
+    fs::write(Path::new(output_dir).join("styles.css"), stylesheet)?;
+    log::info!("Using theme '{}'", theme);
+    Ok(())
+}
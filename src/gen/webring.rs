@@ -1,13 +1,18 @@
+use chrono::{Local, NaiveDate};
 use rand::{seq::SliceRandom, thread_rng};
 use regex::Regex;
 use std::collections::HashSet;
 
+use crate::blocklist::{load_blocklist, Blocklist};
 use crate::cli::AppSettings;
 use crate::error::Error;
 use crate::file::parse_website_list;
-use crate::gen::{html::HtmlGenerator, Generator};
+use crate::gen::{
+    gemini::GeminiGenerator, gopher::GopherGenerator, html::HtmlGenerator, json::JsonGenerator,
+    onionring::OnionringGenerator, server::ServerConfigGenerator, Generator,
+};
 use crate::http::setup_client;
-use crate::website::{audit_links, Website};
+use crate::website::{audit_links, MemberStatus, Website};
 
 #[derive(Debug, serde::Serialize)]
 pub struct WebringSite {
@@ -18,11 +23,15 @@ pub struct WebringSite {
 
 pub struct WebringSiteList {
     pub sites: Vec<WebringSite>,
-    pub failed_sites: Vec<Website>
+    pub failed_sites: Vec<Website>,
+    /// Members taking a break, who are listed but not linked into the ring
+    pub paused_sites: Vec<Website>,
+    /// Members with an `active_from` date still to come
+    pub upcoming_sites: Vec<Website>,
 }
 
-/// Checks each Website to ensure it has a valid URL, and tries to detect duplicate entries.
-pub fn verify_websites(websites: &[Website]) -> Result<(), Error> {
+/// Checks each Website to ensure it has a valid URL and isn't blocked, and tries to detect duplicate entries.
+pub fn verify_websites(websites: &[Website], blocklist: &Blocklist) -> Result<(), Error> {
     let mut slugs = HashSet::new();
     let mut urls = HashSet::new();
 
@@ -30,6 +39,14 @@ pub fn verify_websites(websites: &[Website]) -> Result<(), Error> {
         .map_err(|e| Error::StringError(e.to_string()))?;
 
     for website in websites {
+        if let Some(entry) = blocklist.find(website) {
+            return Err(Error::StringError(format!(
+                "Blocked site: {} - {} matches {}",
+                website.url,
+                website.slug,
+                entry.describe()
+            )));
+        }
         // Check for invalid URL format
         if !url_pattern.is_match(&website.url) {
             return Err(Error::StringError(format!(
@@ -37,6 +54,22 @@ pub fn verify_websites(websites: &[Website]) -> Result<(), Error> {
                 website.url, website.slug
             )));
         }
+        if let Some(gemini_url) = website.gemini_url.as_deref().filter(|url| !url.is_empty()) {
+            if !gemini_url.starts_with("gemini://") {
+                return Err(Error::StringError(format!(
+                    "Unrecognized Gemini URL format: {} - {}",
+                    gemini_url, website.slug
+                )));
+            }
+        }
+        if let Some(gopher_url) = website.gopher_url.as_deref().filter(|url| !url.is_empty()) {
+            if !gopher_url.starts_with("gopher://") {
+                return Err(Error::StringError(format!(
+                    "Unrecognized Gopher URL format: {} - {}",
+                    gopher_url, website.slug
+                )));
+            }
+        }
         // Check for duplicate names and URLs
         if !slugs.insert(&website.slug) {
             return Err(Error::StringError(format!(
@@ -56,6 +89,82 @@ pub fn verify_websites(websites: &[Website]) -> Result<(), Error> {
     Ok(())
 }
 
+/// Gets the website list ready for the audit, the same way for builds and `ringfairy check`:
+/// leaves out blocked sites (if set to), fills in the slugs, and verifies the entries (offline)
+pub fn prepare_websites(
+    mut websites: Vec<Website>,
+    blocklist: &Blocklist,
+    settings: &AppSettings,
+) -> Result<Vec<Website>, Error> {
+    if settings.require_verified && settings.verification_secret.trim().is_empty() {
+        return Err(Error::StringError(
+            "require_verified needs a verification_secret to work out each site's ownership token".to_string(),
+        ));
+    }
+    if settings.require_verified && settings.no_slug {
+        return Err(Error::StringError(
+            "require_verified can't be used with no_slug, since tokens come from slugs and numbered slugs change with the ring order".to_string(),
+        ));
+    }
+
+    // Blocked sites are left out even when skipping verification, so they can't slip in by accident
+    if settings.blocklist_drop || settings.skip_verify {
+        websites = blocklist.drop_blocked(websites);
+    }
+
+    // Missing slugs are filled in up front, so verification & the audit see the ones the pages will use.
+    // Numbered slugs are only provisional here: they're numbered again in ring order once it's known.
+    assign_slugs(&mut websites, settings.no_slug);
+
+    // Verify websites entries if required (offline)
+    if !settings.skip_verify {
+        log::info!("Verifying sites...");
+        verify_websites(&websites, blocklist)?;
+        log::info!("All site entries verified.");
+    }
+
+    Ok(websites)
+}
+
+/// Fills in each site's slug: sequential numbers if `no_slug` is set, otherwise derived from the URL where it's missing
+pub fn assign_slugs(websites: &mut [Website], no_slug: bool) {
+    for (index, website) in websites.iter_mut().enumerate() {
+        if no_slug {
+            // determine sequential slugs if no_slug is true
+            website.slug = (index + 1).to_string();
+        } else if website.slug.is_empty() {
+            website.slug = slug_from_url(&website.url);
+        }
+    }
+}
+
+/// Derives a slug from a URL (minus punctuation)
+pub fn slug_from_url(url: &str) -> String {
+    url.replace(|c: char| !c.is_alphanumeric(), "")
+}
+
+/// Sorts the sites by whether they're in the ring on the given day: the active ones, the paused ones, and the ones yet to start.
+/// Hidden and pending sites, and sites past their `active_until` date, are left out altogether.
+pub fn split_by_status(websites: Vec<Website>, date: NaiveDate) -> (Vec<Website>, Vec<Website>, Vec<Website>) {
+    let mut active = Vec::new();
+    let mut paused = Vec::new();
+    let mut upcoming = Vec::new();
+
+    for website in websites {
+        if website.is_active_on(date) {
+            active.push(website);
+            continue;
+        }
+        match website.status.unwrap_or_default() {
+            MemberStatus::Paused => paused.push(website),
+            MemberStatus::Active if website.active_from.is_some_and(|from| date < from) => upcoming.push(website),
+            _ => log::info!("Leaving out inactive site: {}", website.url),
+        }
+    }
+
+    (active, paused, upcoming)
+}
+
 /// Takes the vec of Websites, and outputs an ordered vec of WebringSites  
 pub async fn build_webring_sequence(
     websites: Vec<Website>,
@@ -69,15 +178,12 @@ pub async fn build_webring_sequence(
         websites.as_mut_slice().shuffle(&mut rng);
     }
 
-    for (index, website) in websites.iter_mut().enumerate() {
-        if settings.no_slug {
-            // determine sequential slugs if no_slug is true
-            website.slug = (index + 1).to_string();
-        } else if website.slug.is_empty() {
-            // if slug is missing, derive it from url (minus punctuation)
-            website.slug = website.url.replace(|c: char| !c.is_alphanumeric(), "");
-        }
-    }
+    // Sites which are paused, hidden, or outside their scheduled dates aren't part of the sequence
+    let today = Local::now().date_naive();
+    websites.retain(|website| website.is_active_on(today));
+
+    // Numbered slugs follow the final ring order, replacing the provisional ones used for verifying
+    assign_slugs(&mut websites, settings.no_slug);
 
     let websites_len = websites.len(); // Capture length before consuming vector
     let mut webring_sites: Vec<WebringSite> = Vec::with_capacity(websites_len);
@@ -106,15 +212,15 @@ pub async fn build_webring_sequence(
 
 /// Based on the provided settings, tries to load a list of websites, then generate & save files to create the webring.
 pub async fn generate_webring_files(settings: &AppSettings) -> Result<(), Error> {
-    let websites = parse_website_list(&settings).await?;
+    let websites = parse_website_list(settings).await?;
     let mut failed_sites: Vec<Website> = Vec::new(); 
 
-    // Verify websites entries if required (offline)
-    if !settings.skip_verify {
-        log::info!("Verifying sites...");
-        verify_websites(&websites)?;
-        log::info!("All site entries verified.");
-    }
+    let blocklist = load_blocklist(settings).await?;
+    let websites = prepare_websites(websites, &blocklist, settings)?;
+
+    // Only the sites which are active today get audited & linked up
+    let (websites, paused_sites, upcoming_sites) =
+        split_by_status(websites, Local::now().date_naive());
 
     // Audit websites to ensure they contain webring links (online)
     let client = setup_client(settings).await?;
@@ -132,6 +238,11 @@ pub async fn generate_webring_files(settings: &AppSettings) -> Result<(), Error>
             websites_len
         );
         audited_websites
+    } else if settings.require_verified {
+        // Without the audit no site has been verified, so none of them can be let in
+        return Err(Error::StringError(
+            "require_verified needs the audit (audit = true, or -A) to check each site's ownership token".to_string(),
+        ));
     } else {
         websites
     };
@@ -143,21 +254,64 @@ pub async fn generate_webring_files(settings: &AppSettings) -> Result<(), Error>
         ));
     }
 
-    // Organize sites into the webring sequence
+    // Organize sites into the webring sequence, leaving out anything admin-only from here on
+    let public = |sites: Vec<Website>| -> Vec<Website> {
+        sites.into_iter().map(|site| site.into_public(&settings.private_fields)).collect()
+    };
     let webring = WebringSiteList {
-        sites: build_webring_sequence(audited_websites, settings).await,
-        failed_sites,
+        sites: build_webring_sequence(public(audited_websites), settings).await,
+        failed_sites: public(failed_sites),
+        paused_sites: public(paused_sites),
+        upcoming_sites: public(upcoming_sites),
     };
 
     // Proceed with HTML generation (if not a dry run)
     if !settings.dry_run {
         log::info!("Generating webring HTML...");
-        let html_generator =
+        let mut html_generator =
             HtmlGenerator::new(settings.path_templates.clone().into(), settings.skip_minify)
                 .await?;
+        html_generator.add_fallback_templates(settings)?;
         html_generator.generate_content(&webring, settings).await?;
         log::info!("Finished generating webring HTML.");
         //html_generator.generate_opml(&webring, &settings).await?;
+
+        let json_generator =
+            JsonGenerator::new(settings.path_templates.clone().into(), settings.skip_minify)
+                .await?;
+        json_generator.generate_content(&webring, settings).await?;
+
+        if !settings.server_configs.is_empty() {
+            log::info!("Generating server redirect configs...");
+            let server_generator =
+                ServerConfigGenerator::new(settings.path_templates.clone().into(), settings.skip_minify)
+                    .await?;
+            server_generator.generate_content(&webring, settings).await?;
+        }
+
+        if settings.onionring {
+            log::info!("Generating onionring files...");
+            let onionring_generator =
+                OnionringGenerator::new(settings.path_templates.clone().into(), settings.skip_minify)
+                    .await?;
+            onionring_generator.generate_content(&webring, settings).await?;
+        }
+
+        if !settings.gemini_base_url.trim().is_empty() {
+            log::info!("Generating Gemini capsule...");
+            let gemini_generator =
+                GeminiGenerator::new(settings.path_templates.clone().into(), settings.skip_minify)
+                    .await?;
+            gemini_generator.generate_content(&webring, settings).await?;
+        }
+
+        if !settings.gopher_base_url.trim().is_empty() {
+            log::info!("Generating gopher hole...");
+            let gopher_generator =
+                GopherGenerator::new(settings.path_templates.clone().into(), settings.skip_minify)
+                    .await?;
+            gopher_generator.generate_content(&webring, settings).await?;
+        }
     }
 
     Ok(())
@@ -1,10 +1,26 @@
 use crate::cli::AppSettings;
 use crate::error::Error;
 
+/// How many redirects the client follows before giving up
+pub const MAX_REDIRECTS: usize = 5;
+
 /// Returns a reqwest client, configured according to the user's configuration settings
 pub async fn setup_client(settings: &AppSettings) -> Result<reqwest::Client, Error> {
     log::trace!("Building reqwest client...");
-    Ok(reqwest::Client::builder()
+    Ok(client_builder(settings)
+        .redirect(reqwest::redirect::Policy::limited(MAX_REDIRECTS))
+        .build()?)
+}
+
+/// Like `setup_client`, but redirects are handed back rather than followed, so they can be reported on
+pub async fn setup_client_without_redirects(settings: &AppSettings) -> Result<reqwest::Client, Error> {
+    Ok(client_builder(settings)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?)
+}
+
+fn client_builder(settings: &AppSettings) -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(30))
         .pool_max_idle_per_host(10)
         .user_agent(settings.client_user_agent.clone())
@@ -16,8 +32,6 @@ pub async fn setup_client(settings: &AppSettings) -> Result<reqwest::Client, Err
             );
             headers
         })
-        .redirect(reqwest::redirect::Policy::limited(5))
-        .build()?)
 }
 
 /// Returns a string from the contents of the given URL (using a temporary/internal reqwest client)
@@ -0,0 +1,256 @@
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cli::AppSettings;
+use crate::error::Error;
+use crate::file::{serialize_website_list, LIST_FORMATS};
+use crate::gen::theme::{theme_stylesheet, DEFAULT_TEMPLATES};
+use crate::website::Website;
+
+/// What `ringfairy init` was told on the command line; anything left out gets asked for (or defaulted)
+pub struct InitAnswers {
+    pub format: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub owner: Option<String>,
+    pub owner_site: Option<String>,
+    pub base_url: Option<String>,
+    pub theme: Option<String>,
+}
+
+/// Everything needed to write out a new ring
+pub struct RingDetails {
+    pub format: String,
+    pub name: String,
+    pub description: String,
+    pub owner: String,
+    pub owner_site: String,
+    pub base_url: String,
+    pub theme: String,
+}
+
+impl InitAnswers {
+    /// Fills in the missing answers, asking on the terminal if `interactive` is set
+    pub fn resolve(self, interactive: bool) -> Result<RingDetails, Error> {
+        let defaults = AppSettings::default();
+        let answer = |given: Option<String>, question: &str, default: &str| match given {
+            Some(value) => Ok(value),
+            None if interactive => ask(question, default),
+            None => Ok(default.to_string()),
+        };
+
+        let details = RingDetails {
+            name: answer(self.name, "Ring name", &defaults.ring_name)?,
+            description: answer(self.description, "Description", &defaults.ring_description)?,
+            owner: answer(self.owner, "Owner", &defaults.ring_owner)?,
+            owner_site: answer(self.owner_site, "Owner's website", &defaults.ring_owner_site)?,
+            base_url: answer(self.base_url, "URL the ring will be hosted at", "https://webring.domain.tld")?,
+            format: answer(self.format, "Website list format (json, toml, yaml, csv)", "json")?
+                .trim()
+                .to_lowercase(),
+            theme: answer(self.theme, "Theme (default, light, terminal)", &defaults.theme)?,
+        };
+
+        if !LIST_FORMATS.contains(&details.format.as_str()) {
+            return Err(Error::StringError(format!(
+                "Unsupported list format '{}' (available: {})",
+                details.format,
+                LIST_FORMATS.join(", ")
+            )));
+        }
+        theme_stylesheet(&details.theme)?;
+
+        Ok(details)
+    }
+}
+
+/// Asks a question on the terminal, with a default for an empty answer
+pub fn ask(question: &str, default: &str) -> Result<String, Error> {
+    print!("{} [{}]: ", question, default);
+    io::stdout().flush()?;
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    let line = line.trim();
+    Ok(if line.is_empty() { default } else { line }.to_string())
+}
+
+/// Quotes a value for the TOML config
+fn toml_string(value: &str) -> String {
+    toml::Value::String(value.to_string()).to_string()
+}
+
+fn build_config(details: &RingDetails, list_file: &str) -> String {
+    format!(
+        r#"# Created by `ringfairy init`. Paths are relative to the folder ringfairy runs in, so run it from here.
+base_url = {base_url}
+ring_name = {name}
+ring_description = {description}
+ring_owner = {owner}
+ring_owner_site = {owner_site}
+
+filepath_list = [{list_file}] # The website list
+path_output = "./webring"           # Generated files will be saved in this folder.
+# path_assets = "./data/assets"     # Create this folder to have its contents copied directly into the output directory
+path_templates = "./data/templates" # The HTML templates, ie, anything with {{{{ tags }}}}. Built-in templates fill in any that are missing
+theme = {theme} # Built-in theme: "default", "light" or "terminal". A styles.css added to the assets folder overrides it
+
+skip_minify = false     # If your generated HTML have issues, or you want hand-editable output, try skip minification
+skip_verify = false     # Skips verification of the URLs in the list. Might be unwise!
+dry_run = false         # Perform a dry run without writing any files. Can be used to check the site audit for example.
+"#,
+        base_url = toml_string(&details.base_url),
+        name = toml_string(&details.name),
+        description = toml_string(&details.description),
+        owner = toml_string(&details.owner),
+        owner_site = toml_string(&details.owner_site),
+        list_file = toml_string(&format!("./{}", list_file)),
+        theme = toml_string(&details.theme),
+    )
+}
+
+fn example_websites() -> Vec<Website> {
+    (1..=2)
+        .map(|n| Website {
+            slug: format!("example{}", n),
+            name: Some(format!("Example {} Site", n)),
+            about: Some(format!("Example Website {}!", n)),
+            url: format!("https://example{}.com", n),
+            owner: Some(format!("owner {}", n)),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Lists the files making up a new ring, as paths relative to its folder
+pub fn scaffold_files(details: &RingDetails) -> Result<Vec<(PathBuf, String)>, Error> {
+    let list_file = format!("websites.{}", details.format);
+    let mut files = vec![
+        (PathBuf::from("ringfairy.toml"), build_config(details, &list_file)),
+        (
+            PathBuf::from(&list_file),
+            serialize_website_list(&example_websites(), &details.format)?,
+        ),
+    ];
+    for (name, content) in DEFAULT_TEMPLATES {
+        files.push((Path::new("data/templates").join(name), content.to_string()));
+    }
+    Ok(files)
+}
+
+/// Writes the files into the folder. Unless `force` is set, nothing gets written if any of them already exist.
+pub fn write_scaffold(directory: &str, files: &[(PathBuf, String)], force: bool) -> Result<(), Error> {
+    let directory = Path::new(directory);
+    if !force {
+        let existing: Vec<String> = files
+            .iter()
+            .map(|(path, _)| directory.join(path))
+            .filter(|path| path.exists())
+            .map(|path| path.display().to_string())
+            .collect();
+        if !existing.is_empty() {
+            return Err(Error::StringError(format!(
+                "Not overwriting existing files (use --force to replace them): {}",
+                existing.join(", ")
+            )));
+        }
+    }
+
+    for (path, content) in files {
+        let path = directory.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, content)?;
+        log::info!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+/// Creates a new ring in the folder
+pub fn init_ring(directory: &str, answers: InitAnswers, no_input: bool, force: bool) -> Result<(), Error> {
+    let details = answers.resolve(!no_input && io::stdin().is_terminal())?;
+    let files = scaffold_files(&details)?;
+    write_scaffold(directory, &files, force)?;
+
+    println!("Created '{}' in {}", details.name, directory);
+    println!(
+        "Add your members to websites.{}, then run `ringfairy -c ringfairy.toml` from that folder.",
+        details.format
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::parse_website_file;
+
+    fn details(format: &str) -> RingDetails {
+        InitAnswers {
+            format: Some(format.into()),
+            name: Some("Test \"Ring\"".into()),
+            description: None,
+            owner: None,
+            owner_site: None,
+            base_url: Some("https://ring.tld".into()),
+            theme: Some("light".into()),
+        }
+        .resolve(false)
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_resolve_rejects_unknown_values() {
+        let answers = |format: &str, theme: &str| InitAnswers {
+            format: Some(format.into()),
+            name: None,
+            description: None,
+            owner: None,
+            owner_site: None,
+            base_url: None,
+            theme: Some(theme.into()),
+        };
+        assert!(answers("xml", "default").resolve(false).is_err());
+        assert!(answers("json", "no-such-theme").resolve(false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scaffold_files_parse() {
+        for format in LIST_FORMATS {
+            let files = scaffold_files(&details(format)).unwrap();
+            let file = |name: &str| &files.iter().find(|(path, _)| path == Path::new(name)).unwrap().1;
+
+            let config: toml::Value = toml::from_str(file("ringfairy.toml")).unwrap();
+            assert_eq!(config["ring_name"].as_str(), Some("Test \"Ring\""));
+            assert_eq!(config["filepath_list"][0].as_str(), Some(format!("./websites.{}", format).as_str()));
+
+            let list = file(&format!("websites.{}", format));
+            let websites = parse_website_file(&format!("websites.{}", format), list).unwrap();
+            assert_eq!(websites, example_websites());
+
+            // The configured theme supplies the stylesheet, so no assets folder is written or configured
+            assert_eq!(config["theme"].as_str(), Some("light"));
+            assert!(files.iter().all(|(path, _)| !path.starts_with("data/assets")));
+            assert!(config.get("path_assets").is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_scaffold_refuses_to_overwrite() {
+        let directory = std::env::temp_dir().join(format!("ringfairy-init-{}", std::process::id()));
+        let directory = directory.to_str().unwrap();
+        let files = scaffold_files(&details("json")).unwrap();
+
+        write_scaffold(directory, &files, false).unwrap();
+        fs::write(Path::new(directory).join("websites.json"), "[]").unwrap();
+
+        assert!(write_scaffold(directory, &files, false).is_err());
+        assert_eq!(fs::read_to_string(Path::new(directory).join("websites.json")).unwrap(), "[]");
+
+        write_scaffold(directory, &files, true).unwrap();
+        assert_ne!(fs::read_to_string(Path::new(directory).join("websites.json")).unwrap(), "[]");
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -1,8 +1,14 @@
+mod blocklist;
+mod check;
 mod cli;
+mod discover;
 mod error;
 mod file;
 mod gen;
 mod http;
+mod init;
+mod members;
+mod review;
 mod website;
 
 #[tokio::main]
@@ -17,8 +23,66 @@ async fn main() -> Result<(), error::Error> {
     // Start a timer
     let start = std::time::Instant::now();
 
-    // Generate webring
-    gen::make_ringfairy_go_now(&settings).await?;
+    match settings.command.clone() {
+        Some(cli::Command::Discover { location, members }) => {
+            discover::discover_ring(&location, members).await?
+        }
+        Some(cli::Command::Init {
+            directory,
+            format,
+            name,
+            description,
+            owner,
+            owner_site,
+            base_url,
+            theme,
+            no_input,
+            force,
+        }) => {
+            let answers = init::InitAnswers {
+                format,
+                name,
+                description,
+                owner,
+                owner_site,
+                base_url,
+                theme,
+            };
+            init::init_ring(&directory, answers, no_input, force)?
+        }
+        Some(
+            command @ (cli::Command::Add { .. }
+            | cli::Command::Remove { .. }
+            | cli::Command::Edit { .. }
+            | cli::Command::Move { .. }),
+        ) => members::manage_members(&command, &settings).await?,
+        Some(cli::Command::Convert {
+            output,
+            format,
+            force,
+        }) => file::convert_website_list(&settings, &output, format.as_deref(), force).await?,
+        Some(cli::Command::Import {
+            from,
+            source,
+            output,
+            format,
+            force,
+        }) => {
+            let imported = file::import_website_list(&from, &source).await?;
+            for skipped in &imported.skipped {
+                eprintln!("Skipped {}", skipped);
+            }
+            file::write_website_list(&imported.websites, &output, format.as_deref(), force)?
+        }
+        Some(cli::Command::Review { no_audit, no_input }) => {
+            review::review_pending(&settings, no_audit, no_input).await?
+        }
+        Some(cli::Command::Check { site, html }) => {
+            check::check_site(&site, html.as_deref(), &settings).await?
+        }
+        // Generate webring
+        None => gen::make_ringfairy_go_now(&settings).await?,
+    }
 
     // Calculate elapsed time
     let elapsed = start.elapsed();
@@ -0,0 +1,624 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::NaiveDate;
+
+use crate::blocklist::{load_blocklist, Blocklist};
+use crate::cli::{AppSettings, Command, MemberFields};
+use crate::discover::{RING_HUB_PREFIX, RING_MEMBERS_PREFIX};
+use crate::error::Error;
+use crate::file::{
+    get_extension_from_path, parse_website_directory, parse_website_file, serialize_website_list,
+};
+use crate::gen::webring::{assign_slugs, slug_from_url, verify_websites};
+use crate::website::Website;
+
+/// A local website list, loaded for editing
+#[derive(Debug)]
+pub struct ListFile {
+    pub path: String,
+    pub websites: Vec<Website>,
+    /// The file's text and sites as they were loaded, so saving only touches the entries which changed
+    loaded: Option<(String, Vec<Website>)>,
+}
+
+impl ListFile {
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let file_data = fs::read_to_string(path)?;
+        let websites = parse_website_file(path, &file_data)?;
+        Ok(ListFile {
+            path: path.to_string(),
+            websites: websites.clone(),
+            loaded: Some((file_data, websites)),
+        })
+    }
+
+    /// Writes the list back out, in the same format it was read in.
+    /// TOML lists are edited in place, keeping comments & layout. JSON and YAML lists keep their entries' fields as written
+    /// (order, nulls and any extra fields), but are reformatted, and YAML comments are lost. CSV lists are rewritten.
+    pub fn save(&self) -> Result<(), Error> {
+        let format = get_extension_from_path(&self.path).unwrap_or_else(|| "json".into());
+        let edited = match &self.loaded {
+            Some((text, loaded)) => edit_list_text(text, &format, loaded, &self.websites)?,
+            None => None,
+        };
+        let content = match edited {
+            Some(content) => content,
+            None => serialize_website_list(&self.websites, &format)?,
+        };
+        fs::write(&self.path, content)?;
+        Ok(())
+    }
+
+    fn position_of(&self, slug: &str) -> Option<usize> {
+        self.websites.iter().position(|website| website.slug == slug)
+    }
+}
+
+impl MemberFields {
+    /// Copies the given fields onto the site. Empty values clear the field.
+    pub fn apply_to(&self, website: &mut Website) -> Result<(), Error> {
+        let set = |field: &mut Option<String>, value: &Option<String>| {
+            if let Some(value) = value {
+                *field = Some(value.clone()).filter(|value| !value.is_empty());
+            }
+        };
+        if let Some(slug) = self.slug.as_ref().filter(|slug| !slug.is_empty()) {
+            website.slug = slug.clone();
+        }
+        set(&mut website.name, &self.name);
+        set(&mut website.about, &self.about);
+        set(&mut website.owner, &self.owner);
+        set(&mut website.rss, &self.rss);
+        set(&mut website.atom, &self.atom);
+        set(&mut website.gemini_url, &self.gemini_url);
+        set(&mut website.gopher_url, &self.gopher_url);
+
+        if let Some(status) = self.status {
+            website.status = Some(status);
+        }
+        let set_date = |field: &mut Option<NaiveDate>, value: &Option<String>, flag: &str| {
+            if let Some(value) = value {
+                *field = match value.trim() {
+                    "" => None,
+                    date => Some(NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| {
+                        Error::StringError(format!("Invalid date '{}' for --{}, expected YYYY-MM-DD: {}", date, flag, e))
+                    })?),
+                };
+            }
+            Ok::<(), Error>(())
+        };
+        set_date(&mut website.joined, &self.joined, "joined")?;
+        set_date(&mut website.active_from, &self.active_from, "active-from")?;
+        set_date(&mut website.active_until, &self.active_until, "active-until")?;
+        Ok(())
+    }
+}
+
+/// Pairs each site in the edited list with the entry it came from in the loaded list, if any: first by slug & URL together,
+/// then by either one (for sites whose slug or URL was edited). Sites without a match are new.
+fn match_loaded(loaded: &[Website], websites: &[Website]) -> Vec<Option<usize>> {
+    let same_site: [fn(&Website, &Website) -> bool; 2] = [
+        |a, b| a.slug == b.slug && a.url == b.url,
+        |a, b| (!a.slug.is_empty() && a.slug == b.slug) || a.url == b.url,
+    ];
+    let mut used = vec![false; loaded.len()];
+    let mut matches = vec![None; websites.len()];
+    for same in same_site {
+        for (index, website) in websites.iter().enumerate() {
+            if matches[index].is_some() {
+                continue;
+            }
+            let found = (0..loaded.len()).find(|&candidate| !used[candidate] && same(&loaded[candidate], website));
+            if let Some(found) = found {
+                used[found] = true;
+                matches[index] = Some(found);
+            }
+        }
+    }
+    matches
+}
+
+/// The fields to write for a site: every field which differs from `before` (null meaning it was cleared), or all of its non-empty fields for a new site
+fn changed_fields(before: Option<&Website>, after: &Website) -> Result<Vec<(String, serde_json::Value)>, Error> {
+    let before = before.map(serde_json::to_value).transpose()?;
+    let serde_json::Value::Object(after) = serde_json::to_value(after)? else {
+        return Ok(Vec::new());
+    };
+    Ok(after
+        .into_iter()
+        .filter(|(field, value)| match &before {
+            Some(before) => before.get(field) != Some(value),
+            None => !value.is_null(),
+        })
+        .collect())
+}
+
+/// Writes the edited sites into the list's original text, touching only the entries which changed.
+/// Returns None if the list has to be rewritten instead (CSV, or a list whose shape isn't recognised).
+fn edit_list_text(text: &str, format: &str, loaded: &[Website], websites: &[Website]) -> Result<Option<String>, Error> {
+    match format {
+        "json" => {
+            let mut document: serde_json::Value = serde_json::from_str(text)?;
+            let Some(entries) = document.as_array_mut() else {
+                return Ok(None);
+            };
+            if !edit_entries(entries, loaded, websites)? {
+                return Ok(None);
+            }
+            Ok(Some(serde_json::to_string_pretty(&document)? + "\n"))
+        }
+        "yaml" | "yml" => {
+            let mut document: serde_json::Value = serde_yaml_ng::from_str(text)
+                .map_err(|e| Error::StringError(format!("Failed to re-read YAML list: {}", e)))?;
+            let entries = match document.get_mut("websites") {
+                Some(websites) => websites.as_array_mut(),
+                None => document.as_array_mut(),
+            };
+            let Some(entries) = entries else {
+                return Ok(None);
+            };
+            if !edit_entries(entries, loaded, websites)? {
+                return Ok(None);
+            }
+            serde_yaml_ng::to_string(&document)
+                .map(Some)
+                .map_err(|e| Error::StringError(format!("Failed to write YAML list: {}", e)))
+        }
+        "toml" => edit_toml_text(text, loaded, websites),
+        _ => Ok(None),
+    }
+}
+
+/// Rebuilds a JSON/YAML list's entries in the edited order, changing only the fields which were edited.
+/// Returns false if the entries don't line up with the loaded sites.
+fn edit_entries(entries: &mut Vec<serde_json::Value>, loaded: &[Website], websites: &[Website]) -> Result<bool, Error> {
+    if entries.len() != loaded.len() || entries.iter().any(|entry| !entry.is_object()) {
+        return Ok(false);
+    }
+    let originals = std::mem::take(entries);
+    for (website, source) in websites.iter().zip(match_loaded(loaded, websites)) {
+        let mut entry = match source {
+            Some(index) => originals[index].clone(),
+            None => serde_json::Value::Object(serde_json::Map::new()),
+        };
+        if let Some(fields) = entry.as_object_mut() {
+            for (field, value) in changed_fields(source.map(|index| &loaded[index]), website)? {
+                if value.is_null() {
+                    fields.shift_remove(&field);
+                } else {
+                    fields.insert(field, value);
+                }
+            }
+        }
+        entries.push(entry);
+    }
+    Ok(true)
+}
+
+/// Converts a field's value for TOML, which has no null
+fn toml_value(value: &serde_json::Value) -> Option<toml_edit::Value> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::Bool(boolean) => Some((*boolean).into()),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(integer) => Some(integer.into()),
+            None => number.as_f64().map(Into::into),
+        },
+        serde_json::Value::String(string) => Some(string.as_str().into()),
+        serde_json::Value::Array(items) => Some(toml_edit::Value::Array(items.iter().filter_map(toml_value).collect())),
+        serde_json::Value::Object(fields) => Some(toml_edit::Value::InlineTable(
+            fields
+                .iter()
+                .filter_map(|(key, value)| toml_value(value).map(|value| (key.as_str(), value)))
+                .collect(),
+        )),
+    }
+}
+
+/// Edits a TOML list in place, keeping its comments & layout. Handles both `[[websites]]` tables and a bare array of inline tables.
+fn edit_toml_text(text: &str, loaded: &[Website], websites: &[Website]) -> Result<Option<String>, Error> {
+    // Bare arrays get the same `websites = ` prefix as when they're read
+    const PREFIX: &str = "websites = ";
+    let trimmed = text.trim_start();
+    let bare = trimmed.starts_with('[') && !trimmed.starts_with("[[");
+    let source = if bare { format!("{}{}", PREFIX, trimmed) } else { text.to_string() };
+    let mut document: toml_edit::DocumentMut = source
+        .parse()
+        .map_err(|e| Error::StringError(format!("Failed to re-read TOML list: {}", e)))?;
+    let sources = match_loaded(loaded, websites);
+
+    match document.get_mut("websites") {
+        Some(toml_edit::Item::ArrayOfTables(tables)) if tables.len() == loaded.len() => {
+            let mut originals: Vec<toml_edit::Table> = tables.iter().cloned().collect();
+            // Comments at the top of the file (up to a blank line) are part of the first table; they stay at the top
+            let header = originals
+                .first_mut()
+                .and_then(|first| {
+                    let prefix = first.decor().prefix()?.as_str()?.to_string();
+                    let (header, rest) = prefix.split_at(prefix.rfind("\n\n")? + 2);
+                    first.decor_mut().set_prefix(rest);
+                    Some(header.to_string())
+                })
+                .unwrap_or_default();
+            tables.clear();
+            for (website, source) in websites.iter().zip(sources) {
+                let mut table = source.map(|index| originals[index].clone()).unwrap_or_default();
+                if tables.is_empty() && !header.is_empty() {
+                    let prefix = table.decor().prefix().and_then(|prefix| prefix.as_str()).unwrap_or_default();
+                    let prefix = format!("{}{}", header, prefix);
+                    table.decor_mut().set_prefix(prefix);
+                }
+                for (field, value) in changed_fields(source.map(|index| &loaded[index]), website)? {
+                    match toml_value(&value) {
+                        Some(value) => table.insert(&field, toml_edit::Item::Value(value)),
+                        None => table.remove(&field),
+                    };
+                }
+                tables.push(table);
+            }
+            // Tables are written in order of position, so moved sites need renumbering
+            let mut position = 1;
+            renumber_tables(document.as_table_mut(), &mut position);
+        }
+        Some(toml_edit::Item::Value(toml_edit::Value::Array(array)))
+            if array.len() == loaded.len() && array.iter().all(|entry| entry.is_inline_table()) =>
+        {
+            let originals: Vec<toml_edit::Value> = array.iter().cloned().collect();
+            array.clear();
+            for (website, source) in websites.iter().zip(sources) {
+                let mut entry = match source {
+                    Some(index) => originals[index].clone(),
+                    None => {
+                        // New sites are laid out like the one before them
+                        let mut entry = toml_edit::Value::InlineTable(toml_edit::InlineTable::new());
+                        if let Some(previous) = array.iter().last().or(originals.last()) {
+                            *entry.decor_mut() = previous.decor().clone();
+                        }
+                        entry
+                    }
+                };
+                if let Some(table) = entry.as_inline_table_mut() {
+                    for (field, value) in changed_fields(source.map(|index| &loaded[index]), website)? {
+                        match toml_value(&value) {
+                            Some(value) => table.insert(&field, value),
+                            None => table.remove(&field),
+                        };
+                    }
+                    if source.is_none() {
+                        table.fmt();
+                    }
+                }
+                array.push_formatted(entry);
+            }
+        }
+        _ => return Ok(None),
+    }
+
+    let output = document.to_string();
+    if bare {
+        let leading = &text[..text.len() - trimmed.len()];
+        return Ok(Some(format!("{}{}", leading, output.strip_prefix(PREFIX).unwrap_or(&output))));
+    }
+    Ok(Some(output))
+}
+
+/// Numbers the tables in the order they appear in the document, which is the order they're written in
+fn renumber_tables(table: &mut toml_edit::Table, position: &mut usize) {
+    for (_, item) in table.iter_mut() {
+        let children: Vec<&mut toml_edit::Table> = match item {
+            toml_edit::Item::Table(table) => vec![table],
+            toml_edit::Item::ArrayOfTables(tables) => tables.iter_mut().collect(),
+            _ => Vec::new(),
+        };
+        for child in children {
+            child.set_position(*position);
+            *position += 1;
+            renumber_tables(child, position);
+        }
+    }
+}
+
+/// Loads the list files which can be edited, ie, the local ones. Folders of member files are edited by hand instead.
+pub fn load_list_files(settings: &AppSettings) -> Result<Vec<ListFile>, Error> {
+    let files = settings
+        .filepath_list
+        .iter()
+        .filter(|path| {
+            !path.starts_with("http://")
+                && !path.starts_with("https://")
+                && !path.starts_with(RING_MEMBERS_PREFIX)
+                && !path.starts_with(RING_HUB_PREFIX)
+                && !Path::new(path).is_dir()
+        })
+        .map(|path| ListFile::load(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if files.is_empty() {
+        return Err(Error::StringError(
+            "No local website list file to edit; set one with --list or 'filepath_list' (folders of member files are edited by hand)".to_string(),
+        ));
+    }
+    Ok(files)
+}
+
+/// Finds which list the site is in, and where
+fn find_member(files: &[ListFile], slug: &str) -> Result<(usize, usize), Error> {
+    files
+        .iter()
+        .enumerate()
+        .find_map(|(file_index, file)| file.position_of(slug).map(|index| (file_index, index)))
+        .ok_or_else(|| Error::StringError(format!("No site with the slug '{}' in the website list", slug)))
+}
+
+/// Turns a position counting from 1 into an index, checking that it fits in a list of `len` items
+fn index_from_position(position: usize, len: usize) -> Result<usize, Error> {
+    if position == 0 || position > len {
+        return Err(Error::StringError(format!(
+            "Position {} is out of range; it should be between 1 and {}",
+            position, len
+        )));
+    }
+    Ok(position - 1)
+}
+
+/// Adds a site to the first list. Returns the index of the list that changed.
+pub fn add_member(
+    files: &mut [ListFile],
+    url: &str,
+    fields: &MemberFields,
+    position: Option<usize>,
+) -> Result<usize, Error> {
+    let mut website = Website {
+        slug: slug_from_url(url),
+        url: url.to_string(),
+        ..Default::default()
+    };
+    fields.apply_to(&mut website)?;
+
+    let list = &mut files[0].websites;
+    let index = match position {
+        Some(position) => index_from_position(position, list.len() + 1)?,
+        None => list.len(),
+    };
+    list.insert(index, website);
+    Ok(0)
+}
+
+/// Removes a site. Returns the index of the list that changed.
+pub fn remove_member(files: &mut [ListFile], slug: &str) -> Result<usize, Error> {
+    let (file_index, index) = find_member(files, slug)?;
+    files[file_index].websites.remove(index);
+    Ok(file_index)
+}
+
+/// Changes a site's details. Returns the index of the list that changed.
+pub fn edit_member(
+    files: &mut [ListFile],
+    slug: &str,
+    url: Option<&str>,
+    fields: &MemberFields,
+) -> Result<usize, Error> {
+    let (file_index, index) = find_member(files, slug)?;
+    let website = &mut files[file_index].websites[index];
+    if let Some(url) = url {
+        website.url = url.to_string();
+    }
+    fields.apply_to(website)?;
+    Ok(file_index)
+}
+
+/// Moves a site within its list. Returns the index of the list that changed.
+pub fn move_member(files: &mut [ListFile], slug: &str, position: usize) -> Result<usize, Error> {
+    let (file_index, index) = find_member(files, slug)?;
+    let list = &mut files[file_index].websites;
+    let new_index = index_from_position(position, list.len())?;
+    let website = list.remove(index);
+    list.insert(new_index, website);
+    Ok(file_index)
+}
+
+/// Checks the lists (plus any members from folders) the same way a build would, with slugs filled in
+fn verify_lists(
+    files: &[ListFile],
+    folder_members: &[Website],
+    blocklist: &Blocklist,
+    settings: &AppSettings,
+) -> Result<(), Error> {
+    let mut websites: Vec<Website> = files.iter().flat_map(|file| file.websites.clone()).collect();
+    websites.extend_from_slice(folder_members);
+    if settings.blocklist_drop {
+        websites = blocklist.drop_blocked(websites);
+    }
+    assign_slugs(&mut websites, settings.no_slug);
+    verify_websites(&websites, blocklist)
+}
+
+/// Runs one of the member commands against the website list(s), saving the list it changed
+pub async fn manage_members(command: &Command, settings: &AppSettings) -> Result<(), Error> {
+    let mut files = load_list_files(settings)?;
+
+    let (changed, message) = match command {
+        Command::Add { url, position, fields } => {
+            let changed = add_member(&mut files, url, fields, *position)?;
+            (changed, format!("Added {}", url))
+        }
+        Command::Remove { slug } => (remove_member(&mut files, slug)?, format!("Removed '{}'", slug)),
+        Command::Edit { slug, url, fields } => (
+            edit_member(&mut files, slug, url.as_deref(), fields)?,
+            format!("Updated '{}'", slug),
+        ),
+        Command::Move { slug, position } => (
+            move_member(&mut files, slug, *position)?,
+            format!("Moved '{}' to position {}", slug, position),
+        ),
+        _ => return Err(Error::StringError("Not a member command".to_string())),
+    };
+
+    let folder_members = settings
+        .filepath_list
+        .iter()
+        .filter(|path| Path::new(path).is_dir())
+        .map(|path| parse_website_directory(path))
+        .collect::<Result<Vec<_>, _>>()?
+        .concat();
+    verify_lists(&files, &folder_members, &load_blocklist(settings).await?, settings)?;
+    files[changed].save()?;
+    println!("{} in {}", message, files[changed].path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list_file(path: &str, slugs: &[&str]) -> ListFile {
+        ListFile {
+            path: path.to_string(),
+            websites: slugs
+                .iter()
+                .map(|slug| Website {
+                    misc: Some(serde_json::json!({ "note": slug })),
+                    ..Website::sample(slug, &format!("https://{}.tld", slug))
+                })
+                .collect(),
+            loaded: None,
+        }
+    }
+
+    fn slugs(file: &ListFile) -> Vec<&str> {
+        file.websites.iter().map(|website| website.slug.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_member_commands() {
+        let settings = AppSettings::default();
+        let mut files = vec![list_file("a.json", &["one", "two"]), list_file("b.json", &["three"])];
+
+        let fields = MemberFields {
+            name: Some("New".into()),
+            ..Default::default()
+        };
+        assert_eq!(add_member(&mut files, "https://new.tld/", &fields, Some(1)).unwrap(), 0);
+        assert_eq!(slugs(&files[0]), vec!["httpsnewtld", "one", "two"]);
+        assert!(verify_lists(&files, &[], &Blocklist::default(), &settings).is_ok());
+
+        // Adding the same URL again fails verification
+        add_member(&mut files, "https://new.tld/", &MemberFields::default(), None).unwrap();
+        assert!(verify_lists(&files, &[], &Blocklist::default(), &settings).is_err());
+        remove_member(&mut files, "httpsnewtld").unwrap();
+        assert!(verify_lists(&files, &[], &Blocklist::default(), &settings).is_ok());
+
+        assert_eq!(move_member(&mut files, "two", 1).unwrap(), 0);
+        assert_eq!(slugs(&files[0]), vec!["two", "one", "httpsnewtld"]);
+        assert!(move_member(&mut files, "two", 4).is_err());
+
+        let fields = MemberFields {
+            slug: Some("3".into()),
+            owner: Some("Someone".into()),
+            ..Default::default()
+        };
+        assert_eq!(edit_member(&mut files, "three", None, &fields).unwrap(), 1);
+        assert_eq!(files[1].websites[0].slug, "3");
+        assert_eq!(files[1].websites[0].owner.as_deref(), Some("Someone"));
+        assert!(files[1].websites[0].misc.is_some(), "Other fields are kept");
+
+        let clear = MemberFields {
+            owner: Some(String::new()),
+            ..Default::default()
+        };
+        edit_member(&mut files, "3", None, &clear).unwrap();
+        assert_eq!(files[1].websites[0].owner, None);
+
+        assert!(remove_member(&mut files, "missing").is_err());
+
+        let pause = MemberFields {
+            status: Some(crate::website::MemberStatus::Paused),
+            active_until: Some("2025-12-31".into()),
+            ..Default::default()
+        };
+        edit_member(&mut files, "3", None, &pause).unwrap();
+        assert_eq!(files[1].websites[0].status, Some(crate::website::MemberStatus::Paused));
+        assert_eq!(files[1].websites[0].active_until, NaiveDate::from_ymd_opt(2025, 12, 31));
+        let bad_date = MemberFields {
+            joined: Some("yesterday".into()),
+            ..Default::default()
+        };
+        assert!(edit_member(&mut files, "3", None, &bad_date).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_member_command_args() {
+        use clap::CommandFactory;
+        crate::cli::ClapSettings::command().debug_assert();
+    }
+
+    #[tokio::test]
+    async fn test_list_file_round_trip() {
+        let directory = std::env::temp_dir().join(format!("ringfairy-members-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        for format in ["json", "toml", "yaml", "csv"] {
+            let path = directory.join(format!("websites.{}", format));
+            let file = list_file(path.to_str().unwrap(), &["one", "two"]);
+            file.save().unwrap();
+
+            let loaded = ListFile::load(path.to_str().unwrap()).unwrap();
+            assert_eq!(loaded.websites, file.websites, "Round trip through {}", format);
+            assert_eq!(
+                loaded.websites[0].misc, file.websites[0].misc,
+                "misc survives {}",
+                format
+            );
+        }
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_save_keeps_list_layout() {
+        let directory = std::env::temp_dir().join(format!("ringfairy-layout-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let edit = |path: &std::path::Path, original: &str| {
+            fs::write(path, original).unwrap();
+            let mut files = vec![ListFile::load(path.to_str().unwrap()).unwrap()];
+            let owner = MemberFields {
+                owner: Some("Someone".into()),
+                ..Default::default()
+            };
+            edit_member(&mut files, "two", None, &owner).unwrap();
+            move_member(&mut files, "three", 1).unwrap();
+            add_member(&mut files, "https://new.tld", &MemberFields::default(), None).unwrap();
+            remove_member(&mut files, "one").unwrap();
+            files[0].save().unwrap();
+
+            let saved = fs::read_to_string(path).unwrap();
+            let reloaded = ListFile::load(path.to_str().unwrap()).unwrap();
+            assert_eq!(reloaded.websites, files[0].websites);
+            saved
+        };
+
+        let toml = "# Our members\n\n[[websites]]\nslug = \"one\"\nurl = \"https://one.tld\"\n\n\
+                    # Joined in spring\n[[websites]]\nslug = \"two\"\nurl = \"https://two.tld\" # their blog\njoined = 2024-04-01\n\n\
+                    [[websites]]\nslug = \"three\"\nurl = \"https://three.tld\"\n\n[websites.misc]\ntags = [\"art\"]\n";
+        let saved = edit(&directory.join("websites.toml"), toml);
+        assert!(saved.starts_with("# Our members\n"), "{}", saved);
+        assert!(saved.contains("# Joined in spring\n[[websites]]\nslug = \"two\"\nurl = \"https://two.tld\" # their blog\njoined = 2024-04-01\nowner = \"Someone\""), "{}", saved);
+        assert!(saved.contains("[websites.misc]\ntags = [\"art\"]"), "{}", saved);
+        assert!(saved.find("\"three\"").unwrap() < saved.find("\"two\"").unwrap(), "{}", saved);
+        assert!(!saved.contains("\"one\""), "{}", saved);
+
+        let bare = "[\n  { slug = \"one\", url = \"https://one.tld\" },\n  { slug = \"two\", url = \"https://two.tld\" },\n  { slug = \"three\", url = \"https://three.tld\" },\n]\n";
+        let saved = edit(&directory.join("bare.toml"), bare);
+        assert!(saved.starts_with("[\n  { slug = \"three\", url = \"https://three.tld\" },\n"), "{}", saved);
+        assert!(saved.contains("\n  { slug = \"httpsnewtld\""), "{}", saved);
+
+        let json = r#"[{"url": "https://one.tld", "slug": "one"}, {"url": "https://two.tld", "slug": "two", "rss": null}, {"url": "https://three.tld", "slug": "three", "extra": 1}]"#;
+        let saved = edit(&directory.join("websites.json"), json);
+        assert!(saved.contains("\"rss\": null"), "Nulls stay as written: {}", saved);
+        assert!(saved.contains("\"extra\": 1"), "{}", saved);
+        assert!(saved.find("\"url\": \"https://three.tld\"").unwrap() < saved.find("\"slug\": \"three\"").unwrap());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,432 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use crate::blocklist::{load_blocklist, Blocklist};
+use crate::cli::AppSettings;
+use crate::discover::{RING_HUB_PREFIX, RING_MEMBERS_PREFIX};
+use crate::error::Error;
+use crate::file::{get_extension_from_path, member_file_paths, parse_member_file, parse_website_list, serialize_member_file};
+use crate::gen::webring::{assign_slugs, verify_websites};
+use crate::http::setup_client;
+use crate::init::ask;
+use crate::members::ListFile;
+use crate::website::{does_html_contain_links, ownership_token, Website, VERIFICATION_META_NAME};
+
+/// Where rejected requests are moved to, inside the pending folder, along with the reasons file
+pub const REJECTED_FOLDER: &str = "rejected";
+pub const REJECTED_REASONS_FILE: &str = "reasons.txt";
+
+/// What the checks made of a join request
+pub struct Review {
+    /// Problems which would break the build, e.g. a duplicate URL. These stop the request being approved.
+    pub verification: Result<(), String>,
+    /// Things worth a look, which don't stop the request being approved
+    pub lint: Vec<String>,
+    /// Whether the site links to the ring, if it was audited
+    pub audit: Option<Result<(), String>>,
+    /// The site's ownership token, and whether the audit found it, if the ring uses them and it was audited
+    pub ownership: Option<(String, bool)>,
+}
+
+impl Review {
+    pub fn can_approve(&self) -> bool {
+        self.verification.is_ok()
+    }
+
+    fn print(&self) {
+        match &self.verification {
+            Ok(()) => println!("  Verification: ok"),
+            Err(e) => println!("  Verification: FAILED - {}", e),
+        }
+        if self.lint.is_empty() {
+            println!("  Lint: ok");
+        }
+        for warning in &self.lint {
+            println!("  Lint: {}", warning);
+        }
+        match &self.audit {
+            Some(Ok(())) => println!("  Audit: ok"),
+            Some(Err(reason)) => println!("  Audit: FAILED - {}", reason),
+            None => println!("  Audit: skipped"),
+        }
+        match &self.ownership {
+            Some((_, true)) => println!("  Ownership: verified"),
+            Some((token, false)) => println!(
+                "  Ownership: NOT verified - expected <meta name=\"{}\" content=\"{}\">",
+                VERIFICATION_META_NAME, token
+            ),
+            None => {}
+        }
+    }
+}
+
+/// Reads a join request, filling in its slug from the URL if it doesn't have one.
+/// Returns None for files which aren't member files.
+pub fn load_submission(path: &Path) -> Result<Option<Website>, Error> {
+    let path_str = path.display().to_string();
+    let Some(ext) = get_extension_from_path(&path_str) else {
+        return Ok(None);
+    };
+    // Like member files, a request without a slug is named after its file
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+    let what = format!("join request '{}'", path_str);
+    Ok(parse_member_file(&fs::read_to_string(path)?, &ext, stem, &what)?.map(|(_, website)| website))
+}
+
+/// Points out details which are allowed, but usually a mistake in a join request
+pub fn lint_website(website: &Website) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let missing = |field: &Option<String>| field.as_deref().is_none_or(|value| value.trim().is_empty());
+
+    if missing(&website.name) {
+        warnings.push("No name given".to_string());
+    }
+    if missing(&website.owner) {
+        warnings.push("No owner given".to_string());
+    }
+    if website.url.starts_with("http://") {
+        warnings.push(format!("The URL isn't HTTPS: {}", website.url));
+    }
+    if !website
+        .slug
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        warnings.push(format!(
+            "The slug '{}' has characters other than letters, numbers, '-' and '_', which makes for awkward URLs",
+            website.slug
+        ));
+    }
+    for (kind, feed) in [("RSS", &website.rss), ("Atom", &website.atom)] {
+        if let Some(feed) = feed.as_deref().filter(|feed| !feed.is_empty()) {
+            if !feed.starts_with("http://") && !feed.starts_with("https://") {
+                warnings.push(format!("The {} feed isn't a web URL: {}", kind, feed));
+            }
+        }
+    }
+    warnings
+}
+
+/// Checks the request the same way a build would, alongside the ring's current members
+/// Blocked requests always fail, even if the ring quietly drops blocked sites from its lists.
+pub fn verify_submission(
+    website: &Website,
+    members: &[Website],
+    blocklist: &Blocklist,
+    settings: &AppSettings,
+) -> Result<(), Error> {
+    let mut websites = members.to_vec();
+    if settings.blocklist_drop {
+        websites = blocklist.drop_blocked(websites);
+    }
+    websites.push(website.clone());
+    assign_slugs(&mut websites, settings.no_slug);
+    verify_websites(&websites, blocklist)
+}
+
+/// Runs every check on a join request. The audit is skipped if there's no client.
+pub async fn review_submission(
+    website: &Website,
+    members: &[Website],
+    blocklist: &Blocklist,
+    client: Option<&reqwest::Client>,
+    settings: &AppSettings,
+) -> Review {
+    let mut verified = None;
+    let audit = match client {
+        Some(client) => Some(match does_html_contain_links(client, website, settings).await {
+            Ok((audited, passed, reason)) => {
+                verified = audited.verified;
+                if passed {
+                    Ok(())
+                } else {
+                    Err(reason.unwrap_or_default().trim().to_string())
+                }
+            }
+            Err((_, e)) => Err(e.to_string()),
+        }),
+        None => None,
+    };
+
+    Review {
+        verification: verify_submission(website, members, blocklist, settings).map_err(|e| e.to_string()),
+        lint: lint_website(website),
+        audit,
+        ownership: ownership_token(&website.slug, settings)
+            .filter(|_| client.is_some())
+            .map(|token| (token, verified == Some(true))),
+    }
+}
+
+/// The first local entry in the website list, which approved sites are added to
+fn approval_target(settings: &AppSettings) -> Result<&str, Error> {
+    settings
+        .filepath_list
+        .iter()
+        .find(|path| {
+            !path.starts_with("http://")
+                && !path.starts_with("https://")
+                && !path.starts_with(RING_MEMBERS_PREFIX)
+                && !path.starts_with(RING_HUB_PREFIX)
+        })
+        .map(String::as_str)
+        .ok_or_else(|| {
+            Error::StringError(
+                "No local website list to add approved sites to; set one with --list or 'filepath_list'".to_string(),
+            )
+        })
+}
+
+/// Adds the site to the first local website list, and removes the request. Returns where it went.
+/// If that's a folder of member files, the site is written there in the request's format, named after the slug.
+/// Anything the ring decides for itself (its status, and whether it's verified) is left out of what the submitter wrote.
+pub fn approve_submission(path: &Path, website: &Website, settings: &AppSettings) -> Result<String, Error> {
+    let target = approval_target(settings)?;
+    let website = Website {
+        status: None,
+        provenance: None,
+        verified: None,
+        ..website.clone()
+    };
+
+    let destination = if Path::new(target).is_dir() {
+        let ext = get_extension_from_path(&path.display().to_string()).unwrap_or_default();
+        let destination = Path::new(target).join(format!("{}.{}", website.slug, ext));
+        if destination.exists() {
+            return Err(Error::StringError(format!(
+                "Not overwriting the existing member file {}",
+                destination.display()
+            )));
+        }
+        fs::write(&destination, serialize_member_file(&website, &ext)?)?;
+        destination.display().to_string()
+    } else {
+        let mut list = ListFile::load(target)?;
+        list.websites.push(website);
+        list.save()?;
+        list.path
+    };
+
+    fs::remove_file(path)?;
+    Ok(destination)
+}
+
+/// Moves the request into the rejected folder, and notes down why
+pub fn reject_submission(path: &Path, url: Option<&str>, reason: &str, settings: &AppSettings) -> Result<(), Error> {
+    let rejected = Path::new(&settings.path_pending).join(REJECTED_FOLDER);
+    fs::create_dir_all(&rejected)?;
+
+    let (Some(stem), ext) = (path.file_stem().and_then(|stem| stem.to_str()), path.extension().and_then(|ext| ext.to_str())) else {
+        return Err(Error::StringError(format!("Not a file: {}", path.display())));
+    };
+    // Earlier rejections with the same name are kept, by numbering the new one
+    let name = |suffix: String| match ext {
+        Some(ext) => format!("{}{}.{}", stem, suffix, ext),
+        None => format!("{}{}", stem, suffix),
+    };
+    let file_name = (1..)
+        .map(|n| if n == 1 { name(String::new()) } else { name(format!("-{}", n)) })
+        .find(|name| !rejected.join(name).exists())
+        .expect("some numbered name is free");
+    fs::rename(path, rejected.join(&file_name))?;
+
+    let mut reasons = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(rejected.join(REJECTED_REASONS_FILE))?;
+    writeln!(
+        reasons,
+        "{} {} ({}): {}",
+        chrono::Local::now().format("%Y-%m-%d"),
+        file_name,
+        url.unwrap_or("unreadable"),
+        if reason.is_empty() { "no reason given" } else { reason }
+    )?;
+    Ok(())
+}
+
+/// Asks what to do with a request, then does it. Returns true if it was approved.
+fn decide(path: &Path, website: Option<&Website>, review: Option<&Review>, settings: &AppSettings) -> Result<bool, Error> {
+    let can_approve = website.is_some() && review.is_some_and(Review::can_approve);
+    let question = if can_approve {
+        "Approve, reject or skip? (a/r/s)"
+    } else {
+        "Reject or skip? (r/s)"
+    };
+
+    match ask(question, "s")?.to_lowercase().as_str() {
+        "a" | "approve" if can_approve => {
+            let website = website.expect("approvable requests have a website");
+            let destination = approve_submission(path, website, settings)?;
+            println!("  Approved; added to {}", destination);
+            Ok(true)
+        }
+        "a" | "approve" => {
+            println!("  Requests which fail verification can't be approved; skipping");
+            Ok(false)
+        }
+        "r" | "reject" => {
+            let reason = ask("  Reason", "")?;
+            reject_submission(path, website.map(|website| website.url.as_str()), &reason, settings)?;
+            println!("  Rejected");
+            Ok(false)
+        }
+        _ => {
+            println!("  Skipped");
+            Ok(false)
+        }
+    }
+}
+
+/// Goes through the join requests in the pending folder, showing what the checks make of each, and (unless `no_input` is set) asking whether to approve or reject it
+pub async fn review_pending(settings: &AppSettings, no_audit: bool, no_input: bool) -> Result<(), Error> {
+    let paths: Vec<PathBuf> = if Path::new(&settings.path_pending).is_dir() {
+        member_file_paths(&settings.path_pending)?
+    } else {
+        Vec::new()
+    };
+    if paths.is_empty() {
+        println!("No join requests in {}", settings.path_pending);
+        return Ok(());
+    }
+
+    let mut members = parse_website_list(settings).await?;
+    let blocklist = load_blocklist(settings).await?;
+    let client = if no_audit { None } else { Some(setup_client(settings).await?) };
+    let interactive = !no_input && io::stdin().is_terminal();
+
+    for path in paths {
+        println!("\n{}", path.display());
+        let (website, review) = match load_submission(&path) {
+            Ok(Some(website)) => {
+                println!("  {} ({})", website.url, website.slug);
+                if let Some(private) = &website.private {
+                    println!("  Private: {}", private);
+                }
+                let review = review_submission(&website, &members, &blocklist, client.as_ref(), settings).await;
+                review.print();
+                (Some(website), Some(review))
+            }
+            Ok(None) => {
+                println!("  Not a JSON, TOML, YAML or Markdown file");
+                (None, None)
+            }
+            Err(e) => {
+                println!("  {}", e);
+                (None, None)
+            }
+        };
+
+        if interactive && decide(&path, website.as_ref(), review.as_ref(), settings)? {
+            members.extend(website);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("ringfairy-review-{}-{}", name, std::process::id()));
+        fs::create_dir_all(directory.join("pending")).unwrap();
+        directory
+    }
+
+    #[tokio::test]
+    async fn test_lint_website() {
+        assert!(lint_website(&Website::sample("site", "https://site.tld")).is_empty());
+
+        let mut website = Website::sample("my site", "http://site.tld");
+        website.owner = Some(" ".into());
+        website.rss = Some("site.tld/feed".into());
+        let warnings = lint_website(&website);
+        assert_eq!(warnings.len(), 4, "{:?}", warnings);
+    }
+
+    #[tokio::test]
+    async fn test_verify_submission() {
+        let settings = AppSettings::default();
+        let members = vec![Website::sample("one", "https://one.tld")];
+
+        assert!(verify_submission(&Website::sample("two", "https://two.tld"), &members, &Blocklist::default(), &settings).is_ok());
+        assert!(verify_submission(&Website::sample("two", "https://one.tld"), &members, &Blocklist::default(), &settings).is_err());
+        assert!(verify_submission(&Website::sample("one", "https://two.tld"), &members, &Blocklist::default(), &settings).is_err());
+        assert!(verify_submission(&Website::sample("two", "two.tld"), &members, &Blocklist::default(), &settings).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_approve_into_list_file() {
+        let directory = temp_dir("list");
+        let list = directory.join("websites.json");
+        fs::write(&list, r#"[{"slug": "one", "url": "https://one.tld"}]"#).unwrap();
+        let request = directory.join("pending/two.yaml");
+        fs::write(&request, "url: https://two.tld\nname: Two\n").unwrap();
+
+        let settings = AppSettings {
+            filepath_list: vec!["ring:https://other.tld".into(), list.display().to_string()],
+            path_pending: directory.join("pending").display().to_string(),
+            ..Default::default()
+        };
+        let website = load_submission(&request).unwrap().unwrap();
+        assert_eq!(website.slug, "two");
+
+        approve_submission(&request, &website, &settings).unwrap();
+        assert!(!request.exists());
+        let approved = ListFile::load(list.to_str().unwrap()).unwrap();
+        assert_eq!(approved.websites.len(), 2);
+        assert_eq!(approved.websites[1].name.as_deref(), Some("Two"));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_approve_into_folder_and_reject() {
+        let directory = temp_dir("folder");
+        let members = directory.join("members");
+        fs::create_dir_all(&members).unwrap();
+        let settings = AppSettings {
+            filepath_list: vec![members.display().to_string()],
+            path_pending: directory.join("pending").display().to_string(),
+            ..Default::default()
+        };
+
+        let request = directory.join("pending/request.md");
+        fs::write(&request, "---\nslug: two\nurl: https://two.tld\nstatus: active\nverified: true\n---\nHello").unwrap();
+        let website = load_submission(&request).unwrap().unwrap();
+        approve_submission(&request, &website, &settings).unwrap();
+
+        // The member file is written afresh, without what the ring decides for itself
+        let approved = fs::read_to_string(members.join("two.md")).unwrap();
+        assert!(!approved.contains("status") && !approved.contains("verified"), "{}", approved);
+        let (_, member) = parse_member_file(&approved, "md", "", "two.md").unwrap().unwrap();
+        assert_eq!((member.slug.as_str(), member.about.as_deref()), ("two", Some("Hello")));
+
+        // A second copy would clash with the approved member file
+        fs::write(&request, "---\nslug: two\nurl: https://three.tld\n---\n").unwrap();
+        assert!(approve_submission(&request, &website, &settings).is_err());
+        assert!(request.exists());
+
+        reject_submission(&request, Some("https://three.tld"), "Spam", &settings).unwrap();
+        assert!(!request.exists());
+        let rejected = directory.join("pending").join(REJECTED_FOLDER);
+        assert!(rejected.join("request.md").exists());
+        let reasons = fs::read_to_string(rejected.join(REJECTED_REASONS_FILE)).unwrap();
+        assert!(reasons.trim_end().ends_with("request.md (https://three.tld): Spam"), "{}", reasons);
+
+        // A later request with the same name doesn't replace the earlier one
+        fs::write(&request, "---\nurl: https://four.tld\n---\n").unwrap();
+        reject_submission(&request, Some("https://four.tld"), "Spam again", &settings).unwrap();
+        assert!(fs::read_to_string(rejected.join("request.md")).unwrap().contains("three.tld"));
+        assert!(fs::read_to_string(rejected.join("request-2.md")).unwrap().contains("four.tld"));
+        let reasons = fs::read_to_string(rejected.join(REJECTED_REASONS_FILE)).unwrap();
+        assert!(reasons.trim_end().ends_with("request-2.md (https://four.tld): Spam again"), "{}", reasons);
+
+        // The rejected folder isn't mistaken for a request
+        assert!(member_file_paths(&settings.path_pending).unwrap().is_empty());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
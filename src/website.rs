@@ -1,12 +1,21 @@
+use chrono::NaiveDate;
 use futures::stream::{FuturesUnordered, StreamExt};
-use serde::{Deserialize, Serialize};
+use hmac::{Hmac, Mac};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use sha2::Sha256;
 use tera::Value;
 use std::result::Result;
 
 use crate::cli::AppSettings;
 use crate::error::Error;
+use crate::gen::onionring::{onionring_id, ONIONRING_VARIABLES_PATH, ONIONRING_WIDGET_PATH};
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+/// Where members can put their ownership token: the name of a `<meta>` tag on their page,
+/// or a file (relative to their site's root) with the token on a line of its own
+pub const VERIFICATION_META_NAME: &str = "webring-verification";
+pub const VERIFICATION_FILE_PATH: &str = ".well-known/webring-verification";
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
 pub struct Website {
     pub slug: String,
     pub name: Option<String>,
@@ -15,9 +24,113 @@ pub struct Website {
     pub rss: Option<String>,
     pub atom: Option<String>,
     pub owner: Option<String>,
+    pub gemini_url: Option<String>,
+    pub gopher_url: Option<String>,
+    pub status: Option<MemberStatus>,
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub joined: Option<NaiveDate>,
+    /// The first day the site is in the ring, for members joining on a set date
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub active_from: Option<NaiveDate>,
+    /// The last day the site is in the ring
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub active_until: Option<NaiveDate>,
+    /// Set only when importing another ring, never read from a list, so a hand-written one can't skip the audit
+    #[serde(skip_deserializing)]
+    pub provenance: Option<Provenance>,
+    /// Whether the audit found the site's ownership token, if it looked for one. Never read from a list.
+    #[serde(skip_deserializing)]
+    pub verified: Option<bool>,
     pub misc: Option<Value>,
+    /// Admin-only details, e.g. contact emails & notes. Kept in the website list, but never written to the generated pages or data files.
+    pub private: Option<Value>,
+}
+
+/// Where a member stands. Only active sites (the default) are part of the ring.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, Hash, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum MemberStatus {
+    #[default]
+    Active,
+    /// Taking a break: left out of the ring, but still listed on the hub
+    Paused,
+    /// Left out of the ring, and not listed anywhere
+    Hidden,
+    /// Not admitted yet
+    Pending,
+}
+
+impl Website {
+    /// Whether the site is part of the ring on the given day, going by its status and dates
+    pub fn is_active_on(&self, date: NaiveDate) -> bool {
+        self.status.unwrap_or_default() == MemberStatus::Active
+            && self.active_from.is_none_or(|from| from <= date)
+            && self.active_until.is_none_or(|until| date <= until)
+    }
+
+    /// A member with the usual details filled in, shared by the tests
+    #[cfg(test)]
+    pub fn sample(slug: &str, url: &str) -> Self {
+        Website {
+            slug: slug.to_string(),
+            name: Some(format!("Site {}", slug)),
+            about: Some(format!("About {}", slug)),
+            url: url.to_string(),
+            rss: Some(format!("http://{}.tld/rss", slug)),
+            atom: Some(format!("http://{}.tld/atom.xml", slug)),
+            owner: Some(format!("Owner {}", slug)),
+            ..Default::default()
+        }
+    }
+
+    /// The site as it's shown publicly: without its `private` table, or any `misc` keys named in `private_fields`
+    pub fn into_public(mut self, private_fields: &[String]) -> Self {
+        self.private = None;
+        if let Some(Value::Object(misc)) = &mut self.misc {
+            misc.retain(|key, _| !private_fields.contains(key));
+            if misc.is_empty() {
+                self.misc = None;
+            }
+        }
+        self
+    }
+}
+
+/// Reads a date written as text (e.g. "2024-05-01"), or as an unquoted TOML date
+fn deserialize_date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<NaiveDate>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum DateValue {
+        Text(String),
+        Toml(toml::value::Datetime),
+    }
+
+    match Option::<DateValue>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(DateValue::Text(text)) if text.trim().is_empty() => Ok(None),
+        Some(DateValue::Text(text)) => NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+            .map(Some)
+            .map_err(|e| D::Error::custom(format!("invalid date '{}', expected YYYY-MM-DD: {}", text, e))),
+        Some(DateValue::Toml(datetime)) => datetime
+            .date
+            .and_then(|date| NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into()))
+            .map(Some)
+            .ok_or_else(|| D::Error::custom(format!("invalid date '{}', expected YYYY-MM-DD", datetime))),
+    }
 }
 
+/// Where a site came from, if it was imported from another ring
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, Hash)]
+pub struct Provenance {
+    pub ring_name: String,
+    pub ring_hub: String,
+    /// The site's slug within its own ring
+    pub slug: String,
+    /// True if the site stands for the whole foreign ring, rather than one of its members
+    pub is_ring: bool,
+}
+
+// `verified` is worked out by the audit, and `misc` & `private` can't be hashed, so none of them count towards a site's identity
 impl PartialEq for Website {
     fn eq(&self, other: &Self) -> bool {
         self.slug == other.slug &&
@@ -26,7 +139,14 @@ impl PartialEq for Website {
         self.url == other.url &&
         self.rss == other.rss &&
         self.atom == other.atom &&
-        self.owner == other.owner
+        self.owner == other.owner &&
+        self.gemini_url == other.gemini_url &&
+        self.gopher_url == other.gopher_url &&
+        self.status == other.status &&
+        self.joined == other.joined &&
+        self.active_from == other.active_from &&
+        self.active_until == other.active_until &&
+        self.provenance == other.provenance
     }
 }
 
@@ -40,6 +160,13 @@ impl std::hash::Hash for Website {
         self.rss.hash(state);
         self.atom.hash(state);
         self.owner.hash(state);
+        self.gemini_url.hash(state);
+        self.gopher_url.hash(state);
+        self.status.hash(state);
+        self.joined.hash(state);
+        self.active_from.hash(state);
+        self.active_until.hash(state);
+        self.provenance.hash(state);
     }
 }
 
@@ -48,6 +175,21 @@ pub struct WebsitesTomlFormat {
     pub websites: Vec<Website>,
 }
 
+/// Reduces a URL to its lowercase host (minus 'www.') and path (minus trailing slashes),
+/// so different spellings of the same site can be matched up.
+/// The universal redirect pages do the same thing in JS, so keep them in sync.
+pub fn canonicalize_url(url: &str) -> String {
+    match url::Url::parse(url.trim()) {
+        Ok(parsed) => {
+            let host = parsed.host_str().unwrap_or_default();
+            let host = host.strip_prefix("www.").unwrap_or(host);
+            let port = parsed.port().map(|p| format!(":{}", p)).unwrap_or_default();
+            format!("{}{}{}", host, port, parsed.path().trim_end_matches('/'))
+        }
+        Err(_) => url.trim().trim_end_matches('/').to_lowercase(),
+    }
+}
+
 async fn fetch_website_content(
     client: &reqwest::Client,
     url: &str,
@@ -78,15 +220,25 @@ pub async fn audit_links(
     settings: &AppSettings,
 ) -> Result<Vec<Website>, Error> {
     let mut tasks = FuturesUnordered::new();
+    let mut compliant_sites = Vec::new();
 
     for website in websites {
+        // Members imported from other rings link to their own ring, not this one
+        if website.provenance.as_ref().is_some_and(|p| !p.is_ring) {
+            // They can't show this ring's token either, so they count as unverified
+            if settings.require_verified {
+                log::warn!("Site failed ownership verification: {} | REASON: Imported from another ring", website.url);
+            } else {
+                log::info!("Skipping audit for imported site: {}", website.url);
+                compliant_sites.push(website);
+            }
+            continue;
+        }
         let website_clone = website.clone();
         let client = client.clone();
         tasks.push(async move { does_html_contain_links(&client, &website_clone, settings).await });
     }
 
-    let mut compliant_sites = Vec::new();
-
     // Collect results - unpacking the tuple inside Ok variant
     while let Some(result) = tasks.next().await {
         match result {
@@ -113,27 +265,70 @@ pub async fn does_html_contain_links(
         .map_err(|e| (website.clone(), e.into()))?;
 
     let document = scraper::Html::parse_document(&html);
+    let (website, failure_reason) = audit_document(Some(client), &document, website, settings).await;
+    Ok((website, failure_reason.is_none(), failure_reason))
+}
 
-    // Define selectors for different elements that could be links.
-    let anchor_selector = scraper::Selector::parse("a").map_err(|e| (website.clone(), e.into()))?;
-    let button_selector =
-        scraper::Selector::parse("button").map_err(|e| (website.clone(), e.into()))?;
-    let img_selector = scraper::Selector::parse("img").map_err(|e| (website.clone(), e.into()))?;
+/// Audits a page which has already been fetched: looks for the ring links and the ownership token, noting whether the site is verified.
+/// Returns the reason the site fails, or None if it passes. Without a client, the verification file isn't checked; only the page is.
+pub async fn audit_document(
+    client: Option<&reqwest::Client>,
+    document: &scraper::Html,
+    website: &Website,
+    settings: &AppSettings,
+) -> (Website, Option<String>) {
+    let (next_exists, previous_exists) = find_ring_links(document, website, settings);
+
+    let mut website = website.clone();
+    let token = ownership_token(&website.slug, settings);
+    if let Some(token) = &token {
+        let mut verified = has_verification_meta(document, token);
+        if let (false, Some(client)) = (verified, client) {
+            verified = has_verification_file(client, &website.url, token).await;
+        }
+        website.verified = Some(verified);
+    }
+
+    let mut failure_reason = audit_failure_reason(next_exists, previous_exists);
+    if failure_reason.is_none() && settings.require_verified && website.verified != Some(true) {
+        failure_reason = Some(format!("Missing ownership token {}", token.unwrap_or_default()));
+    }
+    (website, failure_reason)
+}
 
-    let next_link = format!(
+/// The next & previous URLs the audit accepts for a site
+pub fn expected_links(website: &Website, settings: &AppSettings) -> (Vec<String>, Vec<String>) {
+    let base_url = settings.base_url.trim_end_matches('/');
+    let mut next_links = vec![format!(
         "{}/{}/{}",
-        settings.base_url.trim_end_matches('/'),
-        website.slug,
-        settings.next_url_text
-    );
-    let prev_link: String = format!(
+        base_url, website.slug, settings.next_url_text
+    )];
+    let mut prev_links = vec![format!(
         "{}/{}/{}",
-        settings.base_url.trim_end_matches('/'),
-        website.slug,
-        settings.prev_url_text
-    );
+        base_url, website.slug, settings.prev_url_text
+    )];
+
+    // The universal pages work out the site from the referrer, so they're valid for everyone
+    if settings.universal_links {
+        next_links.push(format!("{}/{}", base_url, settings.next_url_text));
+        prev_links.push(format!("{}/{}", base_url, settings.prev_url_text));
+    }
+
+    (next_links, prev_links)
+}
 
-    //log::trace!("Expected next/previous URLs: {}, {}", &next_link, &prev_link);
+/// Looks through a page for the site's next & previous links, returning whether each was found
+pub fn find_ring_links(document: &scraper::Html, website: &Website, settings: &AppSettings) -> (bool, bool) {
+    // Define selectors for different elements that could be links.
+    let (Ok(anchor_selector), Ok(button_selector), Ok(img_selector)) = (
+        scraper::Selector::parse("a"),
+        scraper::Selector::parse("button"),
+        scraper::Selector::parse("img"),
+    ) else {
+        return (false, false);
+    };
+
+    let (next_links, prev_links) = expected_links(website, settings);
 
     let mut next_exists = false;
     let mut previous_exists = false;
@@ -144,9 +339,9 @@ pub async fn does_html_contain_links(
             log::trace!("Comparing link href: {}", href);
 
             let href_trimmed = href.trim_end_matches('/');
-            if href_trimmed == next_link {
+            if next_links.iter().any(|link| href_trimmed == link) {
                 next_exists = true;
-            } else if href_trimmed == prev_link {
+            } else if prev_links.iter().any(|link| href_trimmed == link) {
                 previous_exists = true;
             }
         }
@@ -157,9 +352,9 @@ pub async fn does_html_contain_links(
         for element in document.select(&button_selector) {
             if let Some(onclick) = element.value().attr("onclick") {
                 log::trace!("Checking button onclick: {}", onclick);
-                if onclick.contains(&next_link) {
+                if contains_link(onclick, &next_links) {
                     next_exists = true;
-                } else if onclick.contains(&prev_link) {
+                } else if contains_link(onclick, &prev_links) {
                     previous_exists = true;
                 }
             }
@@ -171,28 +366,137 @@ pub async fn does_html_contain_links(
         for element in document.select(&img_selector) {
             if let Some(onclick) = element.value().attr("onclick") {
                 log::trace!("Checking img onclick: {}", onclick);
-                if onclick.contains(&next_link) {
+                if contains_link(onclick, &next_links) {
                     next_exists = true;
-                } else if onclick.contains(&prev_link) {
+                } else if contains_link(onclick, &prev_links) {
                     previous_exists = true;
                 }
             }
         }
     }
 
-    let result = next_exists && previous_exists;
-    let failure_reason = if !result {
-        let mut reason = String::new();
-        if !next_exists {
-            reason += "Missing next link. ";
-        }
-        if !previous_exists {
-            reason += "Missing previous link. ";
-        }
-        Some(reason)
-    } else {
-        None
+    // The onionring widget draws its links with JS, so look for its embed instead
+    if (!next_exists || !previous_exists)
+        && settings.onionring
+        && has_onionring_widget(document, &website.url, settings)
+    {
+        log::trace!("Found the onionring widget on {}", website.url);
+        next_exists = true;
+        previous_exists = true;
+    }
+
+    (next_exists, previous_exists)
+}
+
+/// Explains a failed audit, or None if both links were found
+pub fn audit_failure_reason(next_exists: bool, previous_exists: bool) -> Option<String> {
+    if next_exists && previous_exists {
+        return None;
+    }
+    let mut reason = String::new();
+    if !next_exists {
+        reason += "Missing next link. ";
+    }
+    if !previous_exists {
+        reason += "Missing previous link. ";
+    }
+    Some(reason)
+}
+
+/// The token a site shows to prove its owner asked to join: an HMAC of its slug, keyed with the ring's secret.
+/// None if the ring doesn't have a secret.
+pub fn ownership_token(slug: &str, settings: &AppSettings) -> Option<String> {
+    let secret = settings.verification_secret.trim();
+    if secret.is_empty() {
+        return None;
+    }
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).ok()?;
+    mac.update(slug.as_bytes());
+    // Half the digest is plenty, and easier to copy around
+    Some(
+        mac.finalize().into_bytes()[..16]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+    )
+}
+
+/// Checks for `<meta name="webring-verification" content="TOKEN">`
+pub fn has_verification_meta(document: &scraper::Html, token: &str) -> bool {
+    let Ok(meta_selector) = scraper::Selector::parse("meta[name][content]") else {
+        return false;
+    };
+    document.select(&meta_selector).any(|element| {
+        element.value().attr("name").is_some_and(|name| name.eq_ignore_ascii_case(VERIFICATION_META_NAME))
+            && element.value().attr("content").map(str::trim) == Some(token)
+    })
+}
+
+/// Checks the verification file at the root of the site, which can list several tokens (e.g. for sites in more than one ring)
+pub async fn has_verification_file(client: &reqwest::Client, site_url: &str, token: &str) -> bool {
+    let Some(file_url) = url::Url::parse(site_url)
+        .and_then(|url| url.join(&format!("/{}", VERIFICATION_FILE_PATH)))
+        .ok()
+    else {
+        return false;
     };
+    log::trace!("Checking for the ownership token at {}", file_url);
+
+    match client.get(file_url).send().await {
+        Ok(response) if response.status().is_success() => response
+            .text()
+            .await
+            .is_ok_and(|text| text.lines().any(|line| line.trim() == token)),
+        _ => false,
+    }
+}
+
+/// Checks for the onionring.js embed: the element the widget draws itself in, and this ring's variables & widget scripts.
+/// Both scripts have to come from the base URL, so another ring's embed doesn't count.
+pub fn has_onionring_widget(document: &scraper::Html, page_url: &str, settings: &AppSettings) -> bool {
+    let (Ok(script_selector), Ok(id_selector)) = (
+        scraper::Selector::parse("script[src]"),
+        scraper::Selector::parse("[id]"),
+    ) else {
+        return false;
+    };
+
+    // Resolve each script's src against the page, minus any cache-busting query
+    let page = url::Url::parse(page_url).ok();
+    let scripts: Vec<String> = document
+        .select(&script_selector)
+        .filter_map(|element| element.value().attr("src"))
+        .map(|src| match page.as_ref().and_then(|page| page.join(src.trim()).ok()) {
+            Some(mut url) => {
+                url.set_query(None);
+                url.set_fragment(None);
+                url.to_string()
+            }
+            None => src.trim().to_string(),
+        })
+        .collect();
+
+    let base_url = settings.base_url.trim().trim_end_matches('/');
+    if base_url.is_empty() {
+        return false;
+    }
+    // Compared the way the scripts were resolved above, e.g. with the host in lowercase
+    let ours = |path: &str| {
+        let url = format!("{}/{}", base_url, path);
+        url::Url::parse(&url).map(|url| url.to_string()).unwrap_or(url)
+    };
+    let has_variables = scripts.contains(&ours(ONIONRING_VARIABLES_PATH));
+    let has_widget = scripts.contains(&ours(ONIONRING_WIDGET_PATH));
+
+    let id = onionring_id(settings);
+    let has_element = document
+        .select(&id_selector)
+        .any(|element| element.value().attr("id") == Some(id.as_str()));
+
+    has_variables && has_widget && has_element
+}
 
-    Ok((website.clone(), result, failure_reason))
+/// Checks whether an onclick handler refers to any of the given links
+pub fn contains_link(onclick: &str, links: &[String]) -> bool {
+    links.iter().any(|link| onclick.contains(link.as_str()))
 }